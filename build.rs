@@ -0,0 +1,24 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Records the commit being built as `AOC_BUILD_COMMIT`, so that the run
+/// history names it wherever the runner is started from.
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    if let Some(commit) = commit.map(|commit| commit.trim().to_owned()) {
+        if !commit.is_empty() {
+            println!("cargo:rustc-env=AOC_BUILD_COMMIT={}", commit);
+        }
+    }
+    // HEAD moves on checkouts, the refs on commits to the current branch.
+    for path in [".git/HEAD", ".git/refs", ".git/packed-refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
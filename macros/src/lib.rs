@@ -7,13 +7,27 @@ use proc_macro2::{Ident, Punct};
 use quote::{format_ident, quote};
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input, ItemStruct,
+    parse_macro_input,
+    punctuated::Punctuated,
+    Expr, FnArg, ItemFn, ItemStruct, Pat, ReturnType, Token, Type,
 };
 
+/// Day of the month in America/New_York, where puzzles unlock at midnight.
 fn get_current_day() -> u32 {
-    let t = Utc::now();
-    let tz_offset = FixedOffset::west_opt(5 * 3600).unwrap();
-    tz_offset.from_utc_datetime(&t.naive_utc()).day()
+    new_york::puzzle_day(Utc::now())
+}
+
+/// Day numbers of the `dayN.rs` files in `src/solution`, in order.
+fn solution_days() -> Vec<u32> {
+    let mut ret = fs::read_dir("./src/solution")
+        .unwrap()
+        .filter_map(|file| {
+            let name = file.unwrap().file_name().into_string().unwrap();
+            name.strip_prefix("day")?.strip_suffix(".rs")?.parse().ok()
+        })
+        .collect::<Vec<u32>>();
+    ret.sort();
+    ret
 }
 
 #[proc_macro]
@@ -80,6 +94,41 @@ pub fn import_all_days(_input: TokenStream) -> TokenStream {
     .into()
 }
 
+/// `dispatch_day!(day, f, args...)` picks a solution at runtime: it expands to
+/// a `match` on `day` that evaluates to `Some(f::<DayN, DayNP1, DayNP2>(args...))`
+/// for every day in `src/solution` and to `None` for the rest. It has to be
+/// used where the day types are in scope.
+#[proc_macro]
+pub fn dispatch_day(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input with Punctuated::<Expr, Token![,]>::parse_terminated)
+        .into_iter()
+        .collect::<Vec<_>>();
+    if args.len() < 2 {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "dispatch_day! expects a day and a function",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let (day, function, rest) = (&args[0], &args[1], &args[2..]);
+    let arms = solution_days().into_iter().map(|n| {
+        let solution = format_ident!("Day{}", n);
+        let p1 = format_ident!("Day{}P1", n);
+        let p2 = format_ident!("Day{}P2", n);
+        quote! {
+            #n => Some(#function::<#solution, #p1, #p2>(#(#rest),*)),
+        }
+    });
+    quote! {
+        match #day {
+            #(#arms)*
+            _ => None,
+        }
+    }
+    .into()
+}
+
 struct ReturnTypeAttributes {
     p1: Ident,
     p2: Ident,
@@ -129,3 +178,132 @@ pub fn return_type(attr: TokenStream, input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+struct MemoizeAttributes {
+    key: Option<Expr>,
+}
+
+impl Parse for MemoizeAttributes {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(MemoizeAttributes { key: None });
+        }
+        let attr_name = input.parse::<Ident>()?;
+        if attr_name != "key" {
+            return Err(syn::Error::new(
+                attr_name.span(),
+                "expected `key = <expression>`",
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(MemoizeAttributes {
+            key: Some(input.parse::<Expr>()?),
+        })
+    }
+}
+
+/// Caches the results of a function or method in a `HashMap`, so that
+/// recursive calls with the same arguments are only computed once.
+///
+/// The cache is keyed by the owned (`to_owned`) values of all arguments, or by
+/// the expression given as `#[memoize(key = ...)]`, which may refer to the
+/// arguments. Functions taking `self` or references must give a key: the
+/// default one could not tell two receivers or referents apart, so the key has
+/// to name whatever the result depends on. Keys must be `Hash + Eq + 'static`
+/// and the return type `Clone + 'static`. Next to `name`, the macro generates
+/// `name_cache_size()` and `name_cache_clear()`. The runner clears every
+/// cache before each part; clear it yourself in between when the key only
+/// identifies a result within part of the input.
+///
+/// The generated code stores caches through `crate::utils::memo`.
+#[proc_macro_attribute]
+pub fn memoize(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let MemoizeAttributes { key } = parse_macro_input!(attr as MemoizeAttributes);
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = parse_macro_input!(input as ItemFn);
+    let ret_type = match &sig.output {
+        ReturnType::Type(_, ret_type) => ret_type.clone(),
+        ReturnType::Default => {
+            return syn::Error::new_spanned(&sig, "memoized functions must return a value")
+                .to_compile_error()
+                .into()
+        }
+    };
+    let key = match key {
+        Some(key) => quote! { #key },
+        None => {
+            let mut arg_keys = Vec::new();
+            for arg in sig.inputs.iter() {
+                match arg {
+                    FnArg::Receiver(_) => {
+                        return syn::Error::new_spanned(
+                            arg,
+                            "memoized methods need a key naming what the result depends on, \
+                             given with #[memoize(key = ...)]",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                    FnArg::Typed(typed) if matches!(typed.ty.as_ref(), Type::Reference(_)) => {
+                        return syn::Error::new_spanned(
+                            typed,
+                            "memoized functions taking references need a key naming what the \
+                             result depends on, given with #[memoize(key = ...)]",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                    FnArg::Typed(typed) => match typed.pat.as_ref() {
+                        Pat::Ident(pat) => {
+                            let ident = &pat.ident;
+                            arg_keys.push(quote! { #ident.to_owned() });
+                        }
+                        _ => {
+                            return syn::Error::new_spanned(
+                                typed,
+                                "arguments of memoized functions must be plain names, \
+                                 or a key must be given with #[memoize(key = ...)]",
+                            )
+                            .to_compile_error()
+                            .into()
+                        }
+                    },
+                }
+            }
+            quote! { (#(#arg_keys,)*) }
+        }
+    };
+    let name = &sig.ident;
+    let size_ident = format_ident!("{}_cache_size", name);
+    let clear_ident = format_ident!("{}_cache_clear", name);
+    let cache_name = quote! { concat!(module_path!(), "::", stringify!(#name)) };
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            let memoize_key = #key;
+            if let Some(ret) = crate::utils::memo::get::<_, #ret_type>(#cache_name, &memoize_key) {
+                return ret;
+            }
+            // The body runs in a closure so that its `return`s end up here.
+            #[allow(clippy::redundant_closure_call)]
+            let ret: #ret_type = (move || -> #ret_type #block)();
+            crate::utils::memo::insert(#cache_name, memoize_key, ::std::clone::Clone::clone(&ret));
+            ret
+        }
+
+        #[allow(dead_code)]
+        #vis fn #size_ident() -> usize {
+            crate::utils::memo::size(#cache_name)
+        }
+
+        #[allow(dead_code)]
+        #vis fn #clear_ident() {
+            crate::utils::memo::clear(#cache_name)
+        }
+    }
+    .into()
+}
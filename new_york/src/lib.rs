@@ -0,0 +1,32 @@
+//! Time in America/New_York, where puzzles unlock at midnight. Shared by the
+//! main crate and the `macros` crate, which cannot depend on each other.
+
+use chrono::prelude::*;
+
+fn nth_sunday(year: i32, month: u32, n: u32) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let to_sunday = (7 - first.weekday().num_days_from_sunday()) % 7;
+    first + chrono::Duration::days((to_sunday + 7 * (n - 1)) as i64)
+}
+
+/// Local time in New York. Daylight saving time runs from 2:00 on the second
+/// Sunday of March to 2:00 on the first Sunday of November, as it has since
+/// 2007.
+pub fn new_york_time(instant: DateTime<Utc>) -> NaiveDateTime {
+    let standard = instant.naive_utc() - chrono::Duration::hours(5);
+    let year = standard.year();
+    let dst_start = nth_sunday(year, 3, 2).and_hms_opt(2, 0, 0).unwrap();
+    // 2:00 daylight time is 1:00 standard time.
+    let dst_end = nth_sunday(year, 11, 1).and_hms_opt(1, 0, 0).unwrap();
+    if dst_start <= standard && standard < dst_end {
+        standard + chrono::Duration::hours(1)
+    } else {
+        standard
+    }
+}
+
+/// Day of the month in New York at `now`, i.e. the latest puzzle during
+/// December.
+pub fn puzzle_day(now: DateTime<Utc>) -> u32 {
+    new_york_time(now).day()
+}
@@ -0,0 +1,196 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::prelude::*;
+
+use super::race::{self, Clock};
+use crate::config::Config;
+
+/// Transient failures get this many attempts in total.
+pub const REQUEST_ATTEMPTS: u32 = 3;
+
+/// File in the cache directory holding when the last request was sent, so
+/// that the minimum interval also holds between runs.
+pub const LAST_REQUEST_FILE: &str = "last-request";
+
+/// A GET request to the site.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+}
+
+pub struct Response {
+    pub status: u16,
+    /// Read as it arrives, so that large bodies need not fit in memory.
+    pub body: Box<dyn Read>,
+}
+
+impl Response {
+    fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+    /// Whether asking again later may succeed.
+    fn is_transient(&self) -> bool {
+        matches!(self.status, 408 | 429 | 500..=599)
+    }
+}
+
+/// What actually sends requests, so that tests can answer them in memory.
+/// Errors are failures to get any response at all, e.g. timeouts.
+pub trait Transport {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error>>;
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error>> {
+        (**self).send(request)
+    }
+}
+
+pub struct HttpTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl HttpTransport {
+    pub fn new(timeout: Duration) -> Result<HttpTransport, Box<dyn Error>> {
+        Ok(HttpTransport {
+            client: reqwest::blocking::Client::builder()
+                .timeout(timeout)
+                .build()?,
+        })
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error>> {
+        let mut builder = self.client.get(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        let response = builder.send()?;
+        Ok(Response {
+            status: response.status().as_u16(),
+            body: Box::new(response),
+        })
+    }
+}
+
+/// The one way to talk to the site. Every request identifies the runner by
+/// its User-Agent, with contact details if configured, waits until the
+/// configured interval has passed since the previous one, including those of
+/// earlier runs, and is retried with backoff on failures that may go away.
+pub struct Client<'a> {
+    transport: Box<dyn Transport + 'a>,
+    clock: Box<dyn Clock + 'a>,
+    user_agent: String,
+    session: Option<String>,
+    interval: Duration,
+    attempts: u32,
+    last_request_path: PathBuf,
+}
+
+impl<'a> Client<'a> {
+    pub fn new(
+        config: &Config,
+        transport: impl Transport + 'a,
+        clock: impl Clock + 'a,
+    ) -> Client<'a> {
+        let user_agent = match &config.contact {
+            Some(contact) => format!("{} ({})", config.user_agent, contact),
+            None => config.user_agent.clone(),
+        };
+        Client {
+            transport: Box::new(transport),
+            clock: Box::new(clock),
+            user_agent,
+            session: config.session.clone(),
+            interval: config.request_interval,
+            attempts: REQUEST_ATTEMPTS,
+            last_request_path: config.cache_dir.join(LAST_REQUEST_FILE),
+        }
+    }
+    /// Gives each request `attempts` attempts instead of `REQUEST_ATTEMPTS`,
+    /// e.g. a single one for callers that retry on their own.
+    pub fn with_attempts(mut self, attempts: u32) -> Client<'a> {
+        self.attempts = attempts;
+        self
+    }
+    pub fn has_session(&self) -> bool {
+        self.session.is_some()
+    }
+    /// Fails unless requests are sent logged in, for pages that are
+    /// personal.
+    pub fn require_session(&self) -> Result<(), String> {
+        match self.session {
+            Some(_) => Ok(()),
+            None => Err(String::from("Cannot find LOGIN_SESSION")),
+        }
+    }
+    fn last_request(&self) -> Option<DateTime<Utc>> {
+        let millis = fs::read_to_string(&self.last_request_path).ok()?;
+        Utc.timestamp_millis_opt(millis.trim().parse().ok()?)
+            .single()
+    }
+    /// Sleeps until the next request is allowed and records it as sent.
+    fn throttle(&self) {
+        if let Some(last) = self.last_request() {
+            let next = last + chrono::Duration::from_std(self.interval).unwrap();
+            if let Ok(wait) = (next - self.clock.now()).to_std() {
+                self.clock.sleep(wait);
+            }
+        }
+        let now = self.clock.now().timestamp_millis().to_string();
+        let recorded = fs::create_dir_all(self.last_request_path.parent().unwrap())
+            .and_then(|_| fs::write(&self.last_request_path, now));
+        if let Err(err) = recorded {
+            eprintln!("Could not record the request time: {}", err);
+        }
+    }
+    fn request(&self, url: &str) -> Request {
+        let mut headers = vec![("User-Agent", self.user_agent.clone())];
+        if let Some(session) = &self.session {
+            headers.push(("Cookie", format!("session={}", session)));
+        }
+        Request {
+            url: url.to_owned(),
+            headers,
+        }
+    }
+    /// Response to a GET of `url`, its body still unread. Only successful
+    /// responses count; other statuses are errors.
+    fn send(&self, url: &str) -> Result<Response, Box<dyn Error>> {
+        let request = self.request(url);
+        // Failures that are worth retrying are the outer error, the rest
+        // end the attempts as the inner one.
+        let response = race::with_backoff(&*self.clock, self.attempts, || {
+            self.throttle();
+            match self.transport.send(&request) {
+                Ok(response) if response.is_success() => Ok(Ok(response)),
+                Ok(response) if response.is_transient() => {
+                    Err(format!("{} returned HTTP {}", url, response.status))
+                }
+                Ok(response) => Ok(Err(format!("{} returned HTTP {}", url, response.status))),
+                Err(err) => Err(format!("{} failed: {}", url, err)),
+            }
+        });
+        Ok(response??)
+    }
+    /// Body of the page at `url`.
+    pub fn get(&self, url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut body = Vec::new();
+        self.send(url)?.body.read_to_end(&mut body)?;
+        Ok(body)
+    }
+    /// Copies the body of the page at `url` into `writer` as it arrives,
+    /// returning its length.
+    pub fn download(&self, url: &str, writer: &mut impl Write) -> Result<u64, Box<dyn Error>> {
+        Ok(io::copy(&mut self.send(url)?.body, writer)?)
+    }
+    pub fn get_text(&self, url: &str) -> Result<String, Box<dyn Error>> {
+        Ok(String::from_utf8(self.get(url)?)?)
+    }
+}
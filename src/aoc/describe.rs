@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use super::client::Client;
+use super::html;
+use crate::config::Config;
+
+/// A puzzle page boiled down to its description and the example inputs
+/// quoted in it. Part two only shows up once part one has been solved by the
+/// account whose session fetched the page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Description {
+    pub markdown: String,
+    pub examples: Vec<String>,
+}
+
+impl Description {
+    pub fn parse(page: &str) -> Option<Description> {
+        let tokens = html::tokenize(page);
+        let articles = html::articles(&tokens);
+        if articles.is_empty() {
+            return None;
+        }
+        Some(Description {
+            markdown: articles
+                .iter()
+                .map(|article| html::to_markdown(article))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            examples: articles
+                .iter()
+                .flat_map(|article| html::preformatted_blocks(article))
+                .collect(),
+        })
+    }
+    pub fn has_part_two(&self) -> bool {
+        mentions_part_two(&self.markdown)
+    }
+}
+
+fn mentions_part_two(markdown: &str) -> bool {
+    markdown.contains("--- Part Two ---")
+}
+
+pub fn fetch_page(config: &Config, client: &Client, day: u32) -> Result<String, Box<dyn Error>> {
+    client.get_text(&config.puzzle_url(day))
+}
+
+/// Description of `day` as Markdown, from the cache next to the input unless
+/// `refresh` is set or the cache is missing part two while a session that
+/// may unlock it is available. Fetching also refreshes the stored examples.
+pub fn describe(
+    config: &Config,
+    client: &Client,
+    day: u32,
+    refresh: bool,
+) -> Result<String, Box<dyn Error>> {
+    let store = config.day_store(day);
+    if !refresh {
+        if let Some(markdown) = store.read_description() {
+            if !client.has_session() || mentions_part_two(&markdown) {
+                return Ok(markdown);
+            }
+        }
+    }
+    let page = fetch_page(config, client, day)?;
+    let description = Description::parse(&page)
+        .ok_or_else(|| format!("No puzzle description found for day {}", day))?;
+    store.save_description(&description.markdown)?;
+    store.save_examples(&description.examples)?;
+    if client.has_session() && !description.has_part_two() {
+        eprintln!("Part two of day {} is not unlocked yet", day);
+    }
+    Ok(description.markdown)
+}
@@ -0,0 +1,400 @@
+//! Just enough HTML handling for puzzle pages: a forgiving tokenizer and a
+//! Markdown renderer for the handful of elements puzzle descriptions use.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+    },
+    End(String),
+    /// Text with entities already decoded.
+    Text(String),
+}
+
+impl Token {
+    fn is_start(&self, tag: &str) -> bool {
+        matches!(self, Token::Start { name, .. } if name == tag)
+    }
+    fn is_end(&self, tag: &str) -> bool {
+        matches!(self, Token::End(name) if name == tag)
+    }
+    fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            Token::Start { attributes, .. } => attributes
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+pub fn decode_entities(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        ret.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity
+                        .strip_prefix('#')
+                        .and_then(|dec| dec.parse().ok())
+                        .and_then(char::from_u32),
+                },
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                ret.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                ret.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    ret.push_str(rest);
+    ret
+}
+
+fn parse_attributes(s: &str) -> Vec<(String, String)> {
+    let mut ret = Vec::new();
+    let mut rest = s.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (raw, remaining) = match after_eq.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = after_eq[1..].find(quote).map_or(after_eq.len(), |i| i + 1);
+                    (&after_eq[1..end], after_eq.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    (&after_eq[..end], &after_eq[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining.trim_start();
+        }
+        if !name.is_empty() {
+            ret.push((name, value));
+        }
+    }
+    ret
+}
+
+/// Splits `html` into tags and text. Comments, doctypes and the contents of
+/// `<script>` and `<style>` are skipped; malformed markup is kept as text.
+pub fn tokenize(html: &str) -> Vec<Token> {
+    let mut ret = Vec::new();
+    let mut rest = html;
+    let push_text = |ret: &mut Vec<Token>, text: &str| {
+        if !text.is_empty() {
+            ret.push(Token::Text(decode_entities(text)));
+        }
+    };
+    while let Some(start) = rest.find('<') {
+        push_text(&mut ret, &rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            ret.push(Token::End(name.trim().to_ascii_lowercase()));
+            continue;
+        }
+        let tag = tag.strip_suffix('/').unwrap_or(tag);
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        if name.is_empty() {
+            push_text(&mut ret, "<");
+            continue;
+        }
+        if name == "script" || name == "style" {
+            let closing = format!("</{}", name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&closing)
+                .and_then(|i| rest[i..].find('>').map(|j| &rest[i + j + 1..]))
+                .unwrap_or("");
+            continue;
+        }
+        ret.push(Token::Start {
+            name,
+            attributes: parse_attributes(&tag[name_end..]),
+        });
+    }
+    push_text(&mut ret, rest);
+    ret
+}
+
+/// Tokens inside each `<article>` element, in document order.
+pub fn articles(tokens: &[Token]) -> Vec<&[Token]> {
+    let mut ret = Vec::new();
+    let mut i = 0;
+    while let Some(start) = tokens[i..].iter().position(|t| t.is_start("article")) {
+        let start = i + start + 1;
+        let end = tokens[start..]
+            .iter()
+            .position(|t| t.is_end("article"))
+            .map_or(tokens.len(), |end| start + end);
+        ret.push(&tokens[start..end]);
+        i = end;
+    }
+    ret
+}
+
+/// Raw text of every `<pre>` block, with markup stripped.
+pub fn preformatted_blocks(tokens: &[Token]) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut current: Option<String> = None;
+    for token in tokens {
+        match token {
+            t if t.is_start("pre") => current = Some(String::new()),
+            t if t.is_end("pre") => ret.extend(current.take()),
+            Token::Text(text) => {
+                if let Some(block) = current.as_mut() {
+                    block.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+    ret
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+/// Wraps `code` in enough backticks that none inside can close the span.
+fn code_span(code: &str) -> String {
+    let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run + 1);
+    let padding = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", fence, padding, code, padding, fence)
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    blocks: Vec<String>,
+    line: String,
+    /// Items of the lists currently open, innermost last.
+    lists: Vec<Vec<String>>,
+    /// Text and whether any of it was emphasized, while inside `<code>`.
+    code: Option<(String, bool)>,
+    pre: Option<String>,
+    links: Vec<Option<String>>,
+}
+
+impl MarkdownWriter {
+    fn push_inline(&mut self, s: &str) {
+        match self.code.as_mut() {
+            Some((code, _)) => code.push_str(s),
+            None => self.line.push_str(s),
+        }
+    }
+    fn push_text(&mut self, text: &str) {
+        if let Some(pre) = self.pre.as_mut() {
+            pre.push_str(text);
+            return;
+        }
+        let mut collapsed = String::with_capacity(text.len());
+        let mut last_space = self.line.is_empty() || self.line.ends_with([' ', '\n']);
+        if let Some((code, _)) = &self.code {
+            last_space = code.is_empty() && last_space;
+        }
+        for c in text.chars() {
+            if c.is_whitespace() && c != '\u{a0}' {
+                if !last_space {
+                    collapsed.push(' ');
+                }
+                last_space = true;
+            } else {
+                collapsed.push(c);
+                last_space = false;
+            }
+        }
+        if self.code.is_some() {
+            self.push_inline(&collapsed);
+        } else {
+            self.push_inline(&escape_markdown(&collapsed));
+        }
+    }
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.line).trim().to_owned();
+        if line.is_empty() {
+            return;
+        }
+        match self.lists.last_mut() {
+            Some(items) => items.push(line),
+            None => self.blocks.push(line),
+        }
+    }
+    fn start(&mut self, token: &Token) {
+        let Token::Start { name, .. } = token else {
+            return;
+        };
+        if self.pre.is_some() {
+            return;
+        }
+        match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let level = name[1..].parse().unwrap_or(1);
+                self.line = format!("{} ", "#".repeat(level));
+            }
+            "p" | "div" => self.flush(),
+            "br" => self.push_inline("  \n"),
+            "ul" | "ol" => {
+                self.flush();
+                self.lists.push(Vec::new());
+            }
+            "li" => {
+                self.flush();
+                self.line = String::from("- ");
+            }
+            "pre" => {
+                self.flush();
+                self.pre = Some(String::new());
+            }
+            "code" => self.code = Some((String::new(), false)),
+            "em" | "strong" | "b" | "i" => match self.code.as_mut() {
+                Some((_, emphasized)) => *emphasized = true,
+                None => self.push_inline("*"),
+            },
+            "a" => {
+                let href = token.attribute("href").map(|href| {
+                    if href.starts_with('/') {
+                        format!("{}{}", super::BASE_URL, href)
+                    } else {
+                        href.to_owned()
+                    }
+                });
+                if href.is_some() {
+                    self.push_inline("[");
+                }
+                self.links.push(href);
+            }
+            _ => {}
+        }
+    }
+    fn end(&mut self, name: &str) {
+        if let Some(pre) = self.pre.as_ref() {
+            if name == "pre" {
+                let mut block = pre.clone();
+                if !block.ends_with('\n') {
+                    block.push('\n');
+                }
+                self.pre = None;
+                self.blocks.push(format!("```\n{}```", block));
+            }
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "li" => self.flush(),
+            "ul" | "ol" => {
+                self.flush();
+                if let Some(items) = self.lists.pop() {
+                    let depth = self.lists.len();
+                    let items = items
+                        .iter()
+                        .map(|item| {
+                            item.lines()
+                                .map(|line| format!("{}{}", "  ".repeat(depth), line))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    match self.lists.last_mut() {
+                        Some(parent) => match parent.last_mut() {
+                            Some(parent_item) => {
+                                parent_item.push('\n');
+                                parent_item.push_str(&items);
+                            }
+                            None => parent.push(items),
+                        },
+                        None => self.blocks.push(items),
+                    }
+                }
+            }
+            "code" => {
+                if let Some((code, emphasized)) = self.code.take() {
+                    let span = code_span(&code);
+                    if emphasized {
+                        self.push_inline(&format!("*{}*", span));
+                    } else {
+                        self.push_inline(&span);
+                    }
+                }
+            }
+            "em" | "strong" | "b" | "i" if self.code.is_none() => self.push_inline("*"),
+            "a" => {
+                if let Some(Some(href)) = self.links.pop() {
+                    self.push_inline(&format!("]({})", href));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Renders a run of tokens, such as the inside of an `<article>`, as
+/// Markdown. Elements without a Markdown counterpart keep only their text.
+pub fn to_markdown(tokens: &[Token]) -> String {
+    let mut writer = MarkdownWriter::default();
+    for token in tokens {
+        match token {
+            Token::Start { .. } => writer.start(token),
+            Token::End(name) => writer.end(name),
+            Token::Text(text) => writer.push_text(text),
+        }
+    }
+    writer.flush();
+    while let Some(items) = writer.lists.pop() {
+        writer.blocks.extend(items);
+    }
+    let mut ret = writer.blocks.join("\n\n");
+    ret.push('\n');
+    ret
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use prettytable::{Cell, Row, Table};
+
+use super::client::Client;
+use super::json::{self, Value};
+use crate::config::Config;
+
+/// The site asks that private leaderboards be fetched at most once every
+/// 15 minutes.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u64,
+    pub last_star_ts: u64,
+    /// Unix times at which each part was solved, by day.
+    pub completion: BTreeMap<u32, [Option<u64>; 2]>,
+}
+
+impl Member {
+    fn parse(value: &Value) -> Result<Member, String> {
+        let field = |key: &str| {
+            value
+                .get(key)
+                .ok_or_else(|| format!("Leaderboard member is missing {}", key))
+        };
+        let number = |key: &str| {
+            field(key)?
+                .as_u64()
+                .ok_or_else(|| format!("Leaderboard member has invalid {}", key))
+        };
+        let id = number("id")?;
+        let mut completion = BTreeMap::new();
+        let days = field("completion_day_level")?
+            .as_object()
+            .ok_or_else(|| format!("Member {} has invalid completion_day_level", id))?;
+        for (day, parts) in days {
+            let day = day
+                .parse::<u32>()
+                .map_err(|_| format!("Member {} has invalid day {}", id, day))?;
+            let mut times = [None; 2];
+            for (part, time) in times.iter_mut().enumerate() {
+                *time = parts
+                    .get(&(part + 1).to_string())
+                    .and_then(|star| star.get("get_star_ts"))
+                    .and_then(Value::as_u64);
+            }
+            completion.insert(day, times);
+        }
+        Ok(Member {
+            id,
+            name: value.get("name").and_then(Value::as_str).map(String::from),
+            stars: number("stars")? as u32,
+            local_score: number("local_score")?,
+            last_star_ts: number("last_star_ts")?,
+            completion,
+        })
+    }
+    /// Name as shown on the site, which hides members without a public name.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+    /// Seconds between getting the first and the second star of `day`.
+    pub fn part_two_delta(&self, day: u32) -> Option<u64> {
+        match self.completion.get(&day)? {
+            [Some(first), Some(second)] => Some(second.saturating_sub(*first)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Leaderboard {
+    pub owner_id: u64,
+    pub event: String,
+    /// Sorted by local score, with earlier last stars breaking ties.
+    pub members: Vec<Member>,
+}
+
+fn format_delta(seconds: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+impl Leaderboard {
+    pub fn parse(text: &str) -> Result<Leaderboard, String> {
+        let root = json::parse(text)?;
+        let members = root
+            .get("members")
+            .and_then(Value::as_object)
+            .ok_or("Leaderboard is missing members")?;
+        let mut members = members
+            .values()
+            .map(Member::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        members.sort_by_key(|member| {
+            (
+                std::cmp::Reverse(member.local_score),
+                member.last_star_ts,
+                member.id,
+            )
+        });
+        Ok(Leaderboard {
+            owner_id: root
+                .get("owner_id")
+                .and_then(Value::as_u64)
+                .ok_or("Leaderboard is missing owner_id")?,
+            event: root
+                .get("event")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned(),
+            members,
+        })
+    }
+    /// Last day anyone got a star on.
+    pub fn last_day(&self) -> u32 {
+        self.members
+            .iter()
+            .flat_map(|member| member.completion.keys())
+            .max()
+            .copied()
+            .unwrap_or(0)
+    }
+    /// Header and one row per member: rank, name, stars, local score, then
+    /// for each day the stars collected and how long part two took after
+    /// part one.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        let days = 1..=self.last_day();
+        let mut header = vec!["#", "Name", "Stars", "Score"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        header.extend(days.clone().map(|day| day.to_string()));
+        let mut ret = vec![header];
+        for (rank, member) in self.members.iter().enumerate() {
+            let mut row = vec![
+                (rank + 1).to_string(),
+                member.display_name(),
+                member.stars.to_string(),
+                member.local_score.to_string(),
+            ];
+            row.extend(days.clone().map(|day| {
+                match (member.completion.get(&day), member.part_two_delta(day)) {
+                    (_, Some(delta)) => format!("** {}", format_delta(delta)),
+                    (Some([Some(_), _]), None) => String::from("*"),
+                    _ => String::new(),
+                }
+            }));
+            ret.push(row);
+        }
+        ret
+    }
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        for row in self.rows() {
+            table.add_row(Row::new(row.iter().map(|cell| Cell::new(cell)).collect()));
+        }
+        table
+    }
+}
+
+pub fn cache_path(data_dir: &Path, id: u64) -> PathBuf {
+    data_dir.join("leaderboards").join(format!("{}.json", id))
+}
+
+pub fn fetch(config: &Config, client: &Client, id: u64) -> Result<String, Box<dyn Error>> {
+    client.require_session()?;
+    let url = format!(
+        "{}/{}/leaderboard/private/view/{}.json",
+        config.base_url, config.year, id
+    );
+    client.get_text(&url)
+}
+
+fn is_fresh(path: &Path, now: SystemTime) -> bool {
+    match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        // A modification time in the future counts as fresh.
+        Ok(modified) => now
+            .duration_since(modified)
+            .map_or(true, |age| age < REFRESH_INTERVAL),
+        Err(_) => false,
+    }
+}
+
+/// Leaderboard `id`, served from the cache directory while it is
+/// younger than `REFRESH_INTERVAL` and fetched again afterwards. A stale
+/// cache is still used, with a warning, if fetching fails.
+pub fn load(
+    config: &Config,
+    client: &Client,
+    id: u64,
+    now: SystemTime,
+) -> Result<Leaderboard, Box<dyn Error>> {
+    let path = cache_path(&config.cache_dir, id);
+    if !is_fresh(&path, now) {
+        // Logged out sessions are redirected to an HTML page, so only
+        // responses that parse are worth caching.
+        let fetched = fetch(config, client, id).and_then(|text| match Leaderboard::parse(&text) {
+            Ok(leaderboard) => Ok((text, leaderboard)),
+            Err(err) => Err(format!(
+                "Leaderboard {} did not return JSON, is LOGIN_SESSION valid? {}",
+                id, err
+            )
+            .into()),
+        });
+        match fetched {
+            Ok((text, leaderboard)) => {
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(&path, text)?;
+                return Ok(leaderboard);
+            }
+            Err(err) if path.exists() => {
+                eprintln!("Using stale leaderboard cache: {}", err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(Leaderboard::parse(&fs::read_to_string(&path)?)?)
+}
@@ -0,0 +1,13 @@
+pub mod client;
+pub mod describe;
+pub mod html;
+pub mod json;
+pub mod leaderboard;
+pub mod race;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+pub const YEAR: u32 = 2023;
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+use std::thread;
+use std::time::Duration;
+
+use chrono::prelude::*;
+
+pub use new_york::{new_york_time, puzzle_day};
+
+/// Source of the current time and of waiting, so that the release logic can
+/// be driven by a fake clock in tests.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+/// Retries wait `INITIAL_RETRY_DELAY` at first, doubling up to
+/// `MAX_RETRY_DELAY`. Fetching the input right at the unlock gets up to
+/// `FETCH_ATTEMPTS` attempts.
+pub const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+pub const FETCH_ATTEMPTS: u32 = 10;
+
+/// The instant New York's clocks show `local`. Times skipped by the switch to
+/// daylight saving time have no such instant and give `None`.
+pub fn from_new_york_time(local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    [5, 4]
+        .into_iter()
+        .map(|hours| {
+            DateTime::from_naive_utc_and_offset(local + chrono::Duration::hours(hours), Utc)
+        })
+        .find(|instant| new_york_time(*instant) == local)
+}
+
+/// When the puzzle for `day` of `year` unlocks: midnight in New York.
+pub fn unlock_time(year: i32, day: u32) -> DateTime<Utc> {
+    let midnight = NaiveDate::from_ymd_opt(year, 12, day)
+        .unwrap_or_else(|| panic!("Invalid puzzle day {}", day))
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    from_new_york_time(midnight).unwrap()
+}
+
+/// Year and day of the first puzzle unlocking after `now`.
+pub fn next_unlock(now: DateTime<Utc>) -> (i32, u32) {
+    let year = new_york_time(now).year();
+    (1..=25)
+        .map(|day| (year, day))
+        .find(|(year, day)| unlock_time(*year, *day) > now)
+        .unwrap_or((year + 1, 1))
+}
+
+/// Time left as shown in the countdown, rounding partial seconds up so that
+/// it only reads zero at the unlock itself.
+pub fn format_countdown(remaining: Duration) -> String {
+    let seconds = remaining.as_secs() + (remaining.subsec_nanos() > 0) as u64;
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    match seconds / 86400 {
+        0 => clock,
+        days => format!("{}d {}", days, clock),
+    }
+}
+
+/// Sleeps until `unlock`, calling `tick` with the time left about once a
+/// second.
+pub fn wait_until(clock: &impl Clock, unlock: DateTime<Utc>, mut tick: impl FnMut(Duration)) {
+    while let Ok(remaining) = (unlock - clock.now()).to_std() {
+        if remaining.is_zero() {
+            break;
+        }
+        tick(remaining);
+        // Sleep to the next whole second so the countdown lands on zero.
+        let step = match remaining.subsec_nanos() {
+            0 => Duration::from_secs(1),
+            nanos => Duration::from_nanos(nanos as u64),
+        };
+        clock.sleep(step.min(remaining));
+    }
+}
+
+/// Calls `attempt` until it succeeds, sleeping with exponential backoff in
+/// between. Gives up with the last error after `attempts` attempts.
+pub fn with_backoff<T, E: Display, C: Clock + ?Sized>(
+    clock: &C,
+    attempts: u32,
+    mut attempt: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut delay = INITIAL_RETRY_DELAY;
+    for remaining_attempts in (0..attempts).rev() {
+        match attempt() {
+            Ok(ret) => return Ok(ret),
+            Err(err) if remaining_attempts > 0 => {
+                eprintln!("Attempt failed ({}), retrying in {:?}", err, delay);
+                clock.sleep(delay);
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!()
+}
@@ -0,0 +1,89 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::client::Client;
+
+pub const DATA_DIR: &str = "data";
+
+/// Everything downloaded for one day: the puzzle input and the rendered
+/// description under `<cache dir>/dayN/`, and the examples taken from it
+/// under `<examples dir>/dayN/`.
+pub struct DayStore {
+    dir: PathBuf,
+    examples_dir: PathBuf,
+}
+
+impl DayStore {
+    pub fn new(cache_dir: impl AsRef<Path>, examples_dir: impl AsRef<Path>, day: u32) -> DayStore {
+        let day_dir = format!("day{}", day);
+        DayStore {
+            dir: cache_dir.as_ref().join(&day_dir),
+            examples_dir: examples_dir.as_ref().join(day_dir),
+        }
+    }
+    pub fn input_path(&self) -> PathBuf {
+        self.dir.join("input.txt")
+    }
+    pub fn description_path(&self) -> PathBuf {
+        self.dir.join("description.md")
+    }
+    pub fn examples_dir(&self) -> &Path {
+        &self.examples_dir
+    }
+    /// Stored examples, numbered from 1 in the order they appear in the
+    /// description.
+    pub fn example_paths(&self) -> io::Result<Vec<PathBuf>> {
+        let mut numbered = Vec::new();
+        match fs::read_dir(self.examples_dir()) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry?.path();
+                    let number = path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .and_then(|stem| stem.parse::<usize>().ok());
+                    if let Some(number) = number {
+                        numbered.push((number, path));
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        numbered.sort();
+        Ok(numbered.into_iter().map(|(_, path)| path).collect())
+    }
+    /// Downloads the input at `url` into `input_path`. The download streams
+    /// into a file next to it first, so an interrupted one never leaves a
+    /// truncated input behind.
+    pub fn download_input(&self, client: &Client, url: &str) -> Result<(), Box<dyn Error>> {
+        client.require_session()?;
+        let partial_path = self.input_path().with_extension("part");
+        fs::create_dir_all(&self.dir)?;
+        let mut partial = BufWriter::new(File::create(&partial_path)?);
+        client.download(url, &mut partial)?;
+        partial.flush()?;
+        fs::rename(partial_path, self.input_path())?;
+        Ok(())
+    }
+    pub fn read_description(&self) -> Option<String> {
+        fs::read_to_string(self.description_path()).ok()
+    }
+    pub fn save_description(&self, markdown: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.description_path(), markdown)
+    }
+    /// Replaces the stored examples with `examples`.
+    pub fn save_examples(&self, examples: &[String]) -> io::Result<()> {
+        for path in self.example_paths()? {
+            fs::remove_file(path)?;
+        }
+        fs::create_dir_all(&self.examples_dir)?;
+        for (i, example) in examples.iter().enumerate() {
+            fs::write(self.examples_dir().join(format!("{}.txt", i + 1)), example)?;
+        }
+        Ok(())
+    }
+}
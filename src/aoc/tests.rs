@@ -0,0 +1,532 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, SystemTime};
+
+use chrono::prelude::*;
+
+use super::client::{self, Client, Request, Response, Transport};
+use super::describe::{self, Description};
+use super::html;
+use super::json::{self, Value};
+use super::leaderboard::{self, Leaderboard};
+use super::race::{self, Clock};
+use crate::config::Config;
+
+macro_rules! fixture {
+    ($file:literal) => {
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/", $file))
+    };
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let root = env::temp_dir().join(format!("aoc2023-test-{}-{}", process::id(), name));
+    fs::remove_dir_all(&root).ok();
+    root
+}
+
+/// Logged out configuration keeping everything under a fresh temporary
+/// directory.
+fn temp_config(name: &str) -> Config {
+    let mut config = Config::default();
+    config.cache_dir = temp_dir(name);
+    config.examples_dir = config.cache_dir.join("examples");
+    config
+}
+
+/// Transport answering requests with canned responses in order, and
+/// failing once it runs out.
+struct FakeTransport {
+    responses: RefCell<VecDeque<Result<Response, String>>>,
+    requests: RefCell<Vec<Request>>,
+}
+
+impl FakeTransport {
+    fn new() -> FakeTransport {
+        FakeTransport {
+            responses: RefCell::new(VecDeque::new()),
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+    fn respond(self, status: u16, body: &str) -> FakeTransport {
+        self.responses.borrow_mut().push_back(Ok(Response {
+            status,
+            body: Box::new(io::Cursor::new(body.as_bytes().to_vec())),
+        }));
+        self
+    }
+    fn fail(self, error: &str) -> FakeTransport {
+        self.responses.borrow_mut().push_back(Err(error.to_owned()));
+        self
+    }
+}
+
+impl Transport for FakeTransport {
+    fn send(&self, request: &Request) -> Result<Response, Box<dyn Error>> {
+        self.requests.borrow_mut().push(request.clone());
+        let response = self.responses.borrow_mut().pop_front();
+        Ok(response.unwrap_or_else(|| Err(String::from("connection refused")))?)
+    }
+}
+
+#[test]
+fn renders_both_parts() {
+    let description = Description::parse(fixture!("describe/day1.html")).unwrap();
+    assert_eq!(description.markdown, fixture!("describe/day1.md"));
+    assert!(description.has_part_two());
+}
+
+#[test]
+fn extracts_examples_from_both_parts() {
+    let description = Description::parse(fixture!("describe/day1.html")).unwrap();
+    assert_eq!(
+        description.examples,
+        vec![
+            "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n",
+            "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n\
+             4nineeightseven2\nzoneight234\n7pqrstsixteen\n",
+        ]
+    );
+}
+
+#[test]
+fn renders_part_one_when_logged_out() {
+    let description = Description::parse(fixture!("describe/day7_logged_out.html")).unwrap();
+    assert_eq!(
+        description.markdown,
+        fixture!("describe/day7_logged_out.md")
+    );
+    assert!(!description.has_part_two());
+    assert_eq!(
+        description.examples,
+        vec!["32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483\n"]
+    );
+}
+
+#[test]
+fn rejects_pages_without_puzzle() {
+    let page = "<html><body><main><p>Please don't repeatedly request this endpoint \
+                before it unlocks!</p></main></body></html>";
+    assert_eq!(Description::parse(page), None);
+}
+
+#[test]
+fn decodes_entities() {
+    assert_eq!(
+        html::decode_entities("a &lt;b&gt; &amp;&#65;&#x42; &bogus; & c"),
+        "a <b> &AB &bogus; & c"
+    );
+}
+
+#[test]
+fn escapes_markdown_outside_code() {
+    let tokens = html::tokenize("<p>a *star* and_under <code>x*y`z</code></p>");
+    assert_eq!(
+        html::to_markdown(&tokens),
+        "a \\*star\\* and\\_under ``x*y`z``\n"
+    );
+}
+
+#[test]
+fn renders_nested_lists_and_links() {
+    let tokens = html::tokenize(
+        "<ul><li>one<ul><li>inner</li></ul></li>\
+         <li><a href=\"/2023/day/2\">two</a></li></ul>",
+    );
+    assert_eq!(
+        html::to_markdown(&tokens),
+        "- one\n  - inner\n- [two](https://adventofcode.com/2023/day/2)\n"
+    );
+}
+
+#[test]
+fn stores_examples_replacing_old_ones() {
+    let config = temp_config("examples");
+    let store = config.day_store(1);
+    let examples = |n: usize| (1..=n).map(|i| format!("{}\n", i)).collect::<Vec<_>>();
+    store.save_examples(&examples(3)).unwrap();
+    store.save_examples(&examples(2)).unwrap();
+    let paths = store.example_paths().unwrap();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "2\n");
+    assert!(paths[1].starts_with(&config.examples_dir));
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
+
+#[test]
+fn describe_uses_cached_description() {
+    let config = temp_config("cache");
+    config.day_store(1).save_description("cached\n").unwrap();
+    let (transport, clock) = (FakeTransport::new(), FakeClock::at("2023-12-01T05:00:00Z"));
+    let client = Client::new(&config, &transport, &clock);
+    assert_eq!(
+        describe::describe(&config, &client, 1, false).unwrap(),
+        "cached\n"
+    );
+    assert!(transport.requests.borrow().is_empty());
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
+
+#[test]
+fn parses_json() {
+    let value =
+        json::parse(r#" {"a": [1, -2.5e1, true, null], "b": "x\"\u00e9\ud83c\udf84"} "#).unwrap();
+    assert_eq!(
+        value.get("a"),
+        Some(&Value::Array(vec![
+            Value::Number(1.0),
+            Value::Number(-25.0),
+            Value::Bool(true),
+            Value::Null
+        ]))
+    );
+    assert_eq!(value.get("b").and_then(Value::as_str), Some("x\"é🎄"));
+    assert!(json::parse("{\"a\": 1,}").is_err());
+    assert!(json::parse("[1] 2").is_err());
+    assert!(json::parse("<!DOCTYPE html>").is_err());
+}
+
+#[test]
+fn writes_json() {
+    let value = json::parse(r#"{"b": [1.5, -2, null], "a": "tab\t\"é\u0001"}"#).unwrap();
+    let written = value.to_string();
+    assert_eq!(written, r#"{"a":"tab\t\"é\u0001","b":[1.5,-2,null]}"#);
+    assert_eq!(json::parse(&written).unwrap(), value);
+}
+
+#[test]
+fn parses_leaderboard() {
+    let leaderboard = Leaderboard::parse(fixture!("leaderboard/private.json")).unwrap();
+    assert_eq!(leaderboard.owner_id, 1234567);
+    assert_eq!(leaderboard.event, "2023");
+    assert_eq!(
+        leaderboard
+            .members
+            .iter()
+            .map(|member| member.display_name())
+            .collect::<Vec<_>>(),
+        vec![
+            "Ada Lovelace",
+            "(anonymous user #2345678)",
+            "Tied Late",
+            "Grace \"Amazing\" Hopper é",
+        ]
+    );
+    let ada = &leaderboard.members[0];
+    assert_eq!((ada.stars, ada.local_score), (5, 27));
+    assert_eq!(ada.part_two_delta(1), Some(1458));
+    assert_eq!(ada.part_two_delta(3), None);
+    assert_eq!(leaderboard.last_day(), 3);
+}
+
+#[test]
+fn renders_leaderboard_rows() {
+    let leaderboard = Leaderboard::parse(fixture!("leaderboard/private.json")).unwrap();
+    let rows = leaderboard.rows();
+    assert_eq!(rows[0], vec!["#", "Name", "Stars", "Score", "1", "2", "3"]);
+    assert_eq!(
+        rows[1],
+        vec![
+            "1",
+            "Ada Lovelace",
+            "5",
+            "27",
+            "** 0:24:18",
+            "** 0:04:51",
+            "*"
+        ]
+    );
+    assert_eq!(
+        rows[2],
+        vec![
+            "2",
+            "(anonymous user #2345678)",
+            "4",
+            "23",
+            "** 3:33:22",
+            "",
+            "** 25:40:00"
+        ]
+    );
+    assert_eq!(rows[4][4..], ["", "", ""]);
+    assert!(leaderboard
+        .to_table()
+        .to_string()
+        .contains("| 3 | Tied Late "));
+}
+
+#[test]
+fn rejects_malformed_leaderboard() {
+    assert!(Leaderboard::parse(r#"{"owner_id": 1, "members": {"1": {"id": 1}}}"#).is_err());
+    assert!(Leaderboard::parse(r#"{"owner_id": 1}"#).is_err());
+}
+
+#[test]
+fn leaderboard_cache_expires() {
+    let config = temp_config("leaderboard");
+    let path = leaderboard::cache_path(&config.cache_dir, 1234567);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, fixture!("leaderboard/private.json")).unwrap();
+    let (transport, clock) = (FakeTransport::new(), FakeClock::at("2023-12-01T05:00:00Z"));
+    let client = Client::new(&config, &transport, &clock);
+    let now = SystemTime::now();
+    let fresh = leaderboard::load(&config, &client, 1234567, now).unwrap();
+    assert_eq!(fresh.members.len(), 4);
+    // Without a session the stale cache is the best there is.
+    let later = now + leaderboard::REFRESH_INTERVAL + Duration::from_secs(1);
+    assert_eq!(
+        leaderboard::load(&config, &client, 1234567, later).unwrap(),
+        fresh
+    );
+    assert!(leaderboard::load(&config, &client, 7654321, now).is_err());
+    assert!(transport.requests.borrow().is_empty());
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
+
+/// Clock whose `sleep` returns at once, moving time forward instead.
+struct FakeClock {
+    now: RefCell<DateTime<Utc>>,
+    sleeps: RefCell<Vec<Duration>>,
+}
+
+impl FakeClock {
+    fn at(rfc3339: &str) -> FakeClock {
+        FakeClock {
+            now: RefCell::new(utc(rfc3339)),
+            sleeps: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+    fn sleep(&self, duration: Duration) {
+        *self.now.borrow_mut() += chrono::Duration::from_std(duration).unwrap();
+        self.sleeps.borrow_mut().push(duration);
+    }
+}
+
+fn utc(rfc3339: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[test]
+fn unlocks_at_new_york_midnight() {
+    assert_eq!(race::unlock_time(2023, 1), utc("2023-12-01T05:00:00Z"));
+    assert_eq!(race::unlock_time(2023, 25), utc("2023-12-25T05:00:00Z"));
+}
+
+#[test]
+fn follows_new_york_daylight_saving() {
+    let local = |rfc3339: &str| race::new_york_time(utc(rfc3339)).to_string();
+    assert_eq!(local("2023-07-04T03:30:00Z"), "2023-07-03 23:30:00");
+    assert_eq!(local("2023-12-01T04:59:59Z"), "2023-11-30 23:59:59");
+    // 2023 switched on March 12 and November 5.
+    assert_eq!(local("2023-03-12T06:59:59Z"), "2023-03-12 01:59:59");
+    assert_eq!(local("2023-03-12T07:00:00Z"), "2023-03-12 03:00:00");
+    assert_eq!(local("2023-11-05T05:59:59Z"), "2023-11-05 01:59:59");
+    assert_eq!(local("2023-11-05T06:00:00Z"), "2023-11-05 01:00:00");
+    let skipped = NaiveDate::from_ymd_opt(2023, 3, 12)
+        .unwrap()
+        .and_hms_opt(2, 30, 0)
+        .unwrap();
+    assert_eq!(race::from_new_york_time(skipped), None);
+}
+
+#[test]
+fn finds_current_and_next_puzzle() {
+    assert_eq!(race::puzzle_day(utc("2023-12-05T04:59:59Z")), 4);
+    assert_eq!(race::puzzle_day(utc("2023-12-05T05:00:00Z")), 5);
+    assert_eq!(race::next_unlock(utc("2023-10-19T12:00:00Z")), (2023, 1));
+    assert_eq!(race::next_unlock(utc("2023-12-05T04:59:59Z")), (2023, 5));
+    assert_eq!(race::next_unlock(utc("2023-12-05T05:00:00Z")), (2023, 6));
+    assert_eq!(race::next_unlock(utc("2023-12-25T05:00:00Z")), (2024, 1));
+}
+
+#[test]
+fn formats_countdown() {
+    assert_eq!(race::format_countdown(Duration::from_secs(59)), "00:00:59");
+    assert_eq!(race::format_countdown(Duration::from_millis(1)), "00:00:01");
+    assert_eq!(
+        race::format_countdown(Duration::from_secs(3723)),
+        "01:02:03"
+    );
+    assert_eq!(
+        race::format_countdown(Duration::from_secs(90061)),
+        "1d 01:01:01"
+    );
+}
+
+#[test]
+fn counts_down_to_unlock() {
+    let clock = FakeClock::at("2023-12-05T04:59:57.250Z");
+    let mut ticks = Vec::new();
+    race::wait_until(&clock, race::unlock_time(2023, 5), |remaining| {
+        ticks.push(race::format_countdown(remaining))
+    });
+    assert_eq!(clock.now(), utc("2023-12-05T05:00:00Z"));
+    assert_eq!(ticks, vec!["00:00:03", "00:00:02", "00:00:01"]);
+    assert_eq!(clock.sleeps.borrow()[0], Duration::from_millis(750));
+}
+
+#[test]
+fn does_not_wait_after_unlock() {
+    let clock = FakeClock::at("2023-12-05T05:00:01Z");
+    race::wait_until(&clock, race::unlock_time(2023, 5), |_| panic!("Waited"));
+    assert!(clock.sleeps.borrow().is_empty());
+}
+
+#[test]
+fn retries_with_backoff() {
+    let clock = FakeClock::at("2023-12-05T05:00:00Z");
+    let mut attempts = 0;
+    let ret = race::with_backoff(&clock, race::FETCH_ATTEMPTS, || {
+        attempts += 1;
+        if attempts < 4 {
+            Err("not unlocked")
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(ret, Ok(4));
+    assert_eq!(
+        *clock.sleeps.borrow(),
+        vec![1, 2, 4]
+            .into_iter()
+            .map(Duration::from_secs)
+            .collect::<Vec<_>>()
+    );
+    let clock = FakeClock::at("2023-12-05T05:00:00Z");
+    assert_eq!(
+        race::with_backoff(&clock, race::FETCH_ATTEMPTS, || Err::<(), _>("down")),
+        Err("down")
+    );
+    let sleeps = clock.sleeps.borrow();
+    assert_eq!(sleeps.len() as u32, race::FETCH_ATTEMPTS - 1);
+    assert_eq!(*sleeps.last().unwrap(), race::MAX_RETRY_DELAY);
+}
+
+fn logged_in_config(name: &str) -> Config {
+    let mut config = temp_config(name);
+    config.session = Some(String::from("cookie"));
+    config.contact = Some(String::from("someone@example.com"));
+    config
+}
+
+#[test]
+fn identifies_itself_to_the_site() {
+    let config = logged_in_config("identify");
+    let transport = FakeTransport::new().respond(200, "input\n");
+    let clock = FakeClock::at("2023-12-01T05:00:00Z");
+    let client = Client::new(&config, &transport, &clock);
+    assert_eq!(client.get_text("https://example.com/").unwrap(), "input\n");
+    assert_eq!(
+        transport.requests.borrow()[0].headers,
+        [
+            (
+                "User-Agent",
+                String::from("aoc2023 runner (someone@example.com)")
+            ),
+            ("Cookie", String::from("session=cookie")),
+        ]
+    );
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
+
+#[test]
+fn spaces_requests_across_runs() {
+    let config = logged_in_config("throttle");
+    let transport = FakeTransport::new().respond(200, "").respond(200, "");
+    let clock = FakeClock::at("2023-12-01T05:00:00Z");
+    Client::new(&config, &transport, &clock)
+        .get("https://example.com/1")
+        .unwrap();
+    *clock.now.borrow_mut() += chrono::Duration::milliseconds(500);
+    // A new client, as in the next run, still waits for the rest.
+    Client::new(&config, &transport, &clock)
+        .get("https://example.com/2")
+        .unwrap();
+    assert_eq!(
+        *clock.sleeps.borrow(),
+        [config.request_interval - Duration::from_millis(500)]
+    );
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
+
+#[test]
+fn retries_transient_failures_only() {
+    let mut config = logged_in_config("retry");
+    config.request_interval = Duration::ZERO;
+    let transport = FakeTransport::new()
+        .respond(503, "")
+        .fail("timed out")
+        .respond(200, "ok")
+        .respond(404, "");
+    let clock = FakeClock::at("2023-12-01T05:00:00Z");
+    let client = Client::new(&config, &transport, &clock);
+    assert_eq!(client.get_text("https://example.com/").unwrap(), "ok");
+    assert_eq!(
+        *clock.sleeps.borrow(),
+        [Duration::from_secs(1), Duration::from_secs(2)]
+    );
+    let error = client.get("https://example.com/missing").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "https://example.com/missing returned HTTP 404"
+    );
+    assert_eq!(transport.requests.borrow().len(), 4);
+    // Running out of responses fails every attempt.
+    assert!(client.get("https://example.com/").is_err());
+    assert_eq!(
+        transport.requests.borrow().len() as u32,
+        4 + client::REQUEST_ATTEMPTS
+    );
+    // Callers retrying on their own can ask for a single attempt.
+    let once = Client::new(&config, &transport, &clock).with_attempts(1);
+    assert!(once.get("https://example.com/").is_err());
+    assert_eq!(
+        transport.requests.borrow().len() as u32,
+        5 + client::REQUEST_ATTEMPTS
+    );
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
+
+#[test]
+fn downloads_input_and_leaderboard() {
+    let config = logged_in_config("download");
+    let transport = FakeTransport::new()
+        .respond(200, "1abc2\n")
+        .respond(200, fixture!("leaderboard/private.json"))
+        .respond(500, "")
+        .respond(500, "")
+        .respond(500, "");
+    let clock = FakeClock::at("2023-12-01T05:00:00Z");
+    let client = Client::new(&config, &transport, &clock);
+    let store = config.day_store(1);
+    store
+        .download_input(&client, "https://example.com/2023/day/1/input")
+        .unwrap();
+    assert_eq!(fs::read_to_string(store.input_path()).unwrap(), "1abc2\n");
+    let now = SystemTime::now();
+    let fetched = leaderboard::load(&config, &client, 1234567, now).unwrap();
+    assert_eq!(
+        transport.requests.borrow()[1].url,
+        "https://adventofcode.com/2023/leaderboard/private/view/1234567.json"
+    );
+    // A site that is down leaves the stale cache in use.
+    let later = now + leaderboard::REFRESH_INTERVAL + Duration::from_secs(1);
+    assert_eq!(
+        leaderboard::load(&config, &client, 1234567, later).unwrap(),
+        fetched
+    );
+    fs::remove_dir_all(&config.cache_dir).unwrap();
+}
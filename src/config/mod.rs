@@ -0,0 +1,331 @@
+pub mod toml;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::aoc;
+use crate::aoc::client::{Client, HttpTransport};
+use crate::aoc::race::SystemClock;
+use crate::aoc::store::DayStore;
+use crate::solution;
+
+pub const DEFAULT_CONFIG_FILE: &str = "aoc.toml";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Plain,
+}
+
+/// Where the effective value of a setting came from, lowest precedence
+/// first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Flag(&'static str),
+}
+
+/// A setting that can be given in the config file under `key`, in the
+/// environment as `env` and on the command line as `flag`.
+struct Setting {
+    key: &'static str,
+    env: &'static str,
+    flag: &'static str,
+    secret: bool,
+}
+
+const SETTINGS: [Setting; 10] = [
+    Setting {
+        key: "base_url",
+        env: "AOC_BASE_URL",
+        flag: "--base-url",
+        secret: false,
+    },
+    Setting {
+        key: "year",
+        env: "AOC_YEAR",
+        flag: "--year",
+        secret: false,
+    },
+    Setting {
+        key: "session",
+        env: "LOGIN_SESSION",
+        flag: "--session",
+        secret: true,
+    },
+    Setting {
+        key: "cache_dir",
+        env: "AOC_CACHE_DIR",
+        flag: "--cache-dir",
+        secret: false,
+    },
+    Setting {
+        key: "examples_dir",
+        env: "AOC_EXAMPLES_DIR",
+        flag: "--examples-dir",
+        secret: false,
+    },
+    Setting {
+        key: "output",
+        env: "AOC_OUTPUT",
+        flag: "--output",
+        secret: false,
+    },
+    Setting {
+        key: "timeout",
+        env: "AOC_TIMEOUT",
+        flag: "--timeout",
+        secret: false,
+    },
+    Setting {
+        key: "user_agent",
+        env: "AOC_USER_AGENT",
+        flag: "--user-agent",
+        secret: false,
+    },
+    Setting {
+        key: "contact",
+        env: "AOC_CONTACT",
+        flag: "--contact",
+        secret: false,
+    },
+    Setting {
+        key: "request_interval",
+        env: "AOC_REQUEST_INTERVAL",
+        flag: "--request-interval",
+        secret: false,
+    },
+];
+
+/// Runner settings, layered from lowest to highest precedence: built-in
+/// defaults, the config file (`--config`, `AOC_CONFIG` or `aoc.toml`), the
+/// environment and command line flags.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub base_url: String,
+    pub year: i32,
+    pub session: Option<String>,
+    /// Downloaded inputs and descriptions, one directory per day.
+    pub cache_dir: PathBuf,
+    /// Examples taken from the descriptions, one directory per day.
+    pub examples_dir: PathBuf,
+    pub output: OutputFormat,
+    /// Timeout for requests to the site.
+    pub timeout: Duration,
+    pub user_agent: String,
+    /// How to reach whoever runs this, added to the User-Agent so the site
+    /// can get in touch about misbehaving traffic.
+    pub contact: Option<String>,
+    /// Minimum time between requests to the site.
+    pub request_interval: Duration,
+    /// Name of the implementation to run by default, by day.
+    pub implementations: BTreeMap<u32, String>,
+    sources: BTreeMap<String, Source>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            base_url: String::from(aoc::BASE_URL),
+            year: aoc::YEAR as i32,
+            session: None,
+            cache_dir: PathBuf::from(aoc::store::DATA_DIR),
+            examples_dir: Path::new(aoc::store::DATA_DIR).join("examples"),
+            output: OutputFormat::Table,
+            timeout: Duration::from_secs(30),
+            user_agent: format!("aoc{} runner", aoc::YEAR),
+            contact: None,
+            request_interval: Duration::from_secs(2),
+            implementations: BTreeMap::new(),
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .skip_while(|arg| *arg != flag)
+        .nth(1)
+        .map(String::as_str)
+}
+
+impl Config {
+    /// Effective configuration for the command line `args`, reading the
+    /// environment through `env`.
+    pub fn load(args: &[String], env: impl Fn(&str) -> Option<String>) -> Result<Config, String> {
+        let mut ret = Config::default();
+        let explicit_path = flag_value(args, "--config")
+            .map(String::from)
+            .or_else(|| env("AOC_CONFIG"));
+        let path = PathBuf::from(explicit_path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE));
+        match fs::read_to_string(&path) {
+            Ok(text) => ret.apply_file(&text, &path)?,
+            // Only a config file that was asked for has to exist.
+            Err(_) if explicit_path.is_none() => {}
+            Err(err) => return Err(format!("Cannot read {}: {}", path.display(), err)),
+        }
+        for setting in &SETTINGS {
+            if let Some(value) = env(setting.env) {
+                ret.set(setting.key, &value, Source::Env(setting.env))?;
+            }
+        }
+        for setting in &SETTINGS {
+            if let Some(value) = flag_value(args, setting.flag) {
+                ret.set(setting.key, value, Source::Flag(setting.flag))?;
+            }
+        }
+        for value in args
+            .windows(2)
+            .filter(|pair| pair[0] == "--implementation")
+            .map(|pair| &pair[1])
+        {
+            let (day, name) = value
+                .split_once('=')
+                .ok_or_else(|| format!("Expected --implementation <day>=<name>, got {}", value))?;
+            ret.set_implementation(day, name, Source::Flag("--implementation"))?;
+        }
+        Ok(ret)
+    }
+    fn apply_file(&mut self, text: &str, path: &Path) -> Result<(), String> {
+        let error = |message: String| format!("{}: {}", path.display(), message);
+        let document = toml::parse(text).map_err(error)?;
+        for (table, entries) in &document {
+            for (key, value) in entries {
+                let source = Source::File(path.to_owned());
+                match table.as_str() {
+                    "" => self.set(key, &value.to_string(), source),
+                    "implementations" => self.set_implementation(key, &value.to_string(), source),
+                    _ => Err(format!("unknown table [{}]", table)),
+                }
+                .map_err(error)?;
+            }
+        }
+        Ok(())
+    }
+    fn set(&mut self, key: &str, value: &str, source: Source) -> Result<(), String> {
+        let invalid =
+            |expected: &str| format!("Invalid {} {:?}, expected {}", key, value, expected);
+        match key {
+            "base_url" => self.base_url = value.trim_end_matches('/').to_owned(),
+            "year" => self.year = value.parse().map_err(|_| invalid("a year"))?,
+            "session" => self.session = Some(value.to_owned()).filter(|s| !s.is_empty()),
+            "cache_dir" => self.cache_dir = PathBuf::from(value),
+            "examples_dir" => self.examples_dir = PathBuf::from(value),
+            "output" => {
+                self.output = match value {
+                    "table" => OutputFormat::Table,
+                    "plain" => OutputFormat::Plain,
+                    _ => return Err(invalid("table or plain")),
+                }
+            }
+            "timeout" => {
+                self.timeout =
+                    Duration::from_secs(value.parse().map_err(|_| invalid("a number of seconds"))?)
+            }
+            "user_agent" => self.user_agent = value.to_owned(),
+            "contact" => self.contact = Some(value.to_owned()).filter(|s| !s.is_empty()),
+            "request_interval" => {
+                self.request_interval =
+                    Duration::from_secs(value.parse().map_err(|_| invalid("a number of seconds"))?)
+            }
+            _ => return Err(format!("Unknown setting {}", key)),
+        }
+        self.sources.insert(key.to_owned(), source);
+        Ok(())
+    }
+    fn set_implementation(&mut self, day: &str, name: &str, source: Source) -> Result<(), String> {
+        let day = day
+            .trim_start_matches("day")
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid day {} for an implementation", day))?;
+        self.implementations.insert(day, name.to_owned());
+        self.sources
+            .insert(format!("implementations.{}", day), source);
+        Ok(())
+    }
+    pub fn source(&self, key: &str) -> &Source {
+        self.sources.get(key).unwrap_or(&Source::Default)
+    }
+    /// Name of the implementation to run for `day`.
+    pub fn implementation(&self, day: u32) -> &str {
+        self.implementations
+            .get(&day)
+            .map_or(solution::DEFAULT_IMPLEMENTATION, String::as_str)
+    }
+    /// Value of setting `key` in config file syntax.
+    fn value(&self, key: &str) -> String {
+        match key {
+            "base_url" => format!("{:?}", self.base_url),
+            "year" => self.year.to_string(),
+            "session" => format!("{:?}", self.session.as_deref().unwrap_or("")),
+            "cache_dir" => format!("{:?}", self.cache_dir.display().to_string()),
+            "examples_dir" => format!("{:?}", self.examples_dir.display().to_string()),
+            "output" => match self.output {
+                OutputFormat::Table => String::from("\"table\""),
+                OutputFormat::Plain => String::from("\"plain\""),
+            },
+            "timeout" => self.timeout.as_secs().to_string(),
+            "user_agent" => format!("{:?}", self.user_agent),
+            "contact" => format!("{:?}", self.contact.as_deref().unwrap_or("")),
+            "request_interval" => self.request_interval.as_secs().to_string(),
+            _ => unreachable!("Unknown setting {}", key),
+        }
+    }
+    /// The effective configuration in config file syntax, each value
+    /// annotated with where it came from. Secrets that are set show up as
+    /// `"<redacted>"`.
+    pub fn show(&self) -> String {
+        let describe = |source: &Source| match source {
+            Source::Default => String::from("default"),
+            Source::File(path) => format!("file {}", path.display()),
+            Source::Env(var) => format!("env {}", var),
+            Source::Flag(flag) => format!("flag {}", flag),
+        };
+        let mut ret = String::new();
+        for setting in &SETTINGS {
+            let mut value = self.value(setting.key);
+            if setting.secret && value != "\"\"" {
+                value = String::from("\"<redacted>\"");
+            }
+            let source = describe(self.source(setting.key));
+            writeln!(ret, "{} = {}  # {}", setting.key, value, source).unwrap();
+        }
+        if !self.implementations.is_empty() {
+            ret.push_str("\n[implementations]\n");
+            for (day, name) in &self.implementations {
+                let source = self.source(&format!("implementations.{}", day));
+                writeln!(ret, "{} = {:?}  # {}", day, name, describe(source)).unwrap();
+            }
+        }
+        ret
+    }
+
+    pub fn day_store(&self, day: u32) -> DayStore {
+        DayStore::new(&self.cache_dir, &self.examples_dir, day)
+    }
+    pub fn puzzle_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{}", self.base_url, self.year, day)
+    }
+    pub fn session(&self) -> Result<&str, String> {
+        self.session
+            .as_deref()
+            .ok_or_else(|| String::from("Cannot find LOGIN_SESSION"))
+    }
+    /// Client for the site, sending requests over HTTP.
+    pub fn client(&self) -> Result<Client<'static>, Box<dyn Error>> {
+        Ok(Client::new(
+            self,
+            HttpTransport::new(self.timeout)?,
+            SystemClock,
+        ))
+    }
+}
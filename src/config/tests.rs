@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use super::toml::{self, Value};
+use super::*;
+
+fn args(args: &[&str]) -> Vec<String> {
+    ["aoc2023"]
+        .iter()
+        .chain(args)
+        .map(|arg| arg.to_string())
+        .collect()
+}
+
+fn env_from<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+    let vars = vars.iter().copied().collect::<HashMap<_, _>>();
+    move |var| vars.get(var).map(|value| value.to_string())
+}
+
+fn temp_config_file(name: &str, text: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("aoc2023-test-{}-{}.toml", process::id(), name));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn parses_toml() {
+    let document = toml::parse(
+        "# runner settings\n\
+         year = 2_022\n\
+         base_url = \"http://localhost\\u003a8080\" # comment\n\
+         \n\
+         [implementations]\n\
+         5 = 'brute-force'\n\
+         \"day 6\".ratio = 0.5\n\
+         enabled = true\n",
+    )
+    .unwrap();
+    assert_eq!(document[""]["year"], Value::Integer(2022));
+    assert_eq!(
+        document[""]["base_url"],
+        Value::String(String::from("http://localhost:8080"))
+    );
+    let implementations = &document["implementations"];
+    assert_eq!(
+        implementations["5"],
+        Value::String(String::from("brute-force"))
+    );
+    assert_eq!(implementations["day 6.ratio"], Value::Float(0.5));
+    assert_eq!(implementations["enabled"], Value::Boolean(true));
+}
+
+#[test]
+fn rejects_malformed_toml() {
+    for (text, error) in [
+        ("year = ", "line 1: unsupported value "),
+        ("a = 1\nb = \"open", "line 2: unterminated string"),
+        ("a = 1\na = 2", "line 2: key a defined twice"),
+        ("[x]\n[x]", "line 2: table x defined twice"),
+        ("[x", "line 1: expected ]"),
+        ("a = 1 2", "line 1: unexpected 2"),
+        ("= 1", "line 1: expected key"),
+    ] {
+        assert_eq!(toml::parse(text).unwrap_err(), error, "{:?}", text);
+    }
+}
+
+#[test]
+fn defaults_without_config_file() {
+    let config = Config::load(&args(&[]), env_from(&[])).unwrap();
+    assert_eq!(config.base_url, aoc::BASE_URL);
+    assert_eq!(config.year, 2023);
+    assert_eq!(config.session, None);
+    assert_eq!(config.output, OutputFormat::Table);
+    assert_eq!(config.implementation(5), solution::DEFAULT_IMPLEMENTATION);
+    assert_eq!(*config.source("year"), Source::Default);
+}
+
+#[test]
+fn layers_file_env_and_flags() {
+    let path = temp_config_file(
+        "layers",
+        "year = 2022\n\
+         timeout = 5\n\
+         output = \"plain\"\n\
+         cache_dir = \"cache\"\n\
+         [implementations]\n\
+         5 = \"brute-force\"\n\
+         6 = \"quadratic\"\n",
+    );
+    let vars = [("AOC_YEAR", "2021"), ("AOC_TIMEOUT", "10")];
+    let config = Config::load(
+        &args(&[
+            "--config",
+            path.to_str().unwrap(),
+            "--year",
+            "2020",
+            "--implementation",
+            "6=default",
+        ]),
+        env_from(&vars),
+    )
+    .unwrap();
+    assert_eq!(config.year, 2020);
+    assert_eq!(config.timeout, Duration::from_secs(10));
+    assert_eq!(config.output, OutputFormat::Plain);
+    assert_eq!(config.cache_dir, Path::new("cache"));
+    assert_eq!(config.implementation(5), "brute-force");
+    assert_eq!(config.implementation(6), "default");
+    assert_eq!(*config.source("year"), Source::Flag("--year"));
+    assert_eq!(*config.source("timeout"), Source::Env("AOC_TIMEOUT"));
+    assert_eq!(*config.source("output"), Source::File(path.clone()));
+    assert_eq!(*config.source("user_agent"), Source::Default);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    let path = temp_config_file("invalid", "colour = \"always\"\n");
+    let error = Config::load(&args(&["--config", path.to_str().unwrap()]), env_from(&[]));
+    assert!(error.unwrap_err().ends_with("Unknown setting colour"));
+    fs::remove_file(path).unwrap();
+    let error = Config::load(&args(&[]), env_from(&[("AOC_OUTPUT", "json")]));
+    assert_eq!(
+        error.unwrap_err(),
+        "Invalid output \"json\", expected table or plain"
+    );
+    // A config file that was asked for has to exist.
+    let error = Config::load(&args(&[]), env_from(&[("AOC_CONFIG", "/nonexistent.toml")]));
+    assert!(error
+        .unwrap_err()
+        .starts_with("Cannot read /nonexistent.toml"));
+}
+
+#[test]
+fn shows_config_with_secrets_redacted() {
+    let vars = [("LOGIN_SESSION", "53616c7465645f5f")];
+    let config = Config::load(
+        &args(&["--implementation", "day5=brute-force"]),
+        env_from(&vars),
+    )
+    .unwrap();
+    let shown = config.show();
+    assert!(!shown.contains("53616c7465645f5f"));
+    assert!(shown.contains("session = \"<redacted>\"  # env LOGIN_SESSION\n"));
+    assert!(shown.contains("year = 2023  # default\n"));
+    assert!(shown.ends_with("[implementations]\n5 = \"brute-force\"  # flag --implementation\n"));
+    // What is shown reads back as the same configuration.
+    let document = toml::parse(&shown).unwrap();
+    assert_eq!(document[""]["year"], Value::Integer(2023));
+    assert_eq!(document[""].len(), 10);
+}
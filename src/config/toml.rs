@@ -0,0 +1,174 @@
+//! Reader for the subset of TOML the config file needs: tables, and keys
+//! holding strings, integers, floats or booleans.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl fmt::Display for Value {
+    /// Formats the value as plain text, e.g. strings without quotes.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", s),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Keys by table, with the keys before any table header under `""`.
+pub type Document = BTreeMap<String, BTreeMap<String, Value>>;
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Reads a quoted string at the start of `s`, returning it together with
+/// the rest of `s`.
+fn parse_string(s: &str) -> Result<(String, &str), String> {
+    if let Some(rest) = s.strip_prefix('\'') {
+        let end = rest.find('\'').ok_or("unterminated string")?;
+        return Ok((rest[..end].to_owned(), &rest[end + 1..]));
+    }
+    let mut chars = s.strip_prefix('"').ok_or("expected string")?.char_indices();
+    let mut ret = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((ret, &s[i + 2..])),
+            '\\' => {
+                let escaped = match chars.next().map(|(_, c)| c) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some(u @ ('u' | 'U')) => {
+                        let len = if u == 'u' { 4 } else { 8 };
+                        let hex = (0..len)
+                            .map(|_| chars.next().map(|(_, c)| c))
+                            .collect::<Option<String>>()
+                            .ok_or("invalid unicode escape")?;
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or("invalid unicode escape")?
+                    }
+                    _ => return Err(String::from("invalid escape")),
+                };
+                ret.push(escaped);
+            }
+            _ => ret.push(c),
+        }
+    }
+    Err(String::from("unterminated string"))
+}
+
+fn parse_key(s: &str) -> Result<(String, &str), String> {
+    let s = s.trim_start();
+    if s.starts_with(['"', '\'']) {
+        return parse_string(s);
+    }
+    let end = s.find(|c| !is_bare_key_char(c)).unwrap_or(s.len());
+    if end == 0 {
+        return Err(String::from("expected key"));
+    }
+    Ok((s[..end].to_owned(), &s[end..]))
+}
+
+/// Dotted key such as `a."b.c".d`, joined back with dots.
+fn parse_dotted_key(s: &str) -> Result<(String, &str), String> {
+    let (mut key, mut rest) = parse_key(s)?;
+    while let Some(after_dot) = rest.trim_start().strip_prefix('.') {
+        let (part, remaining) = parse_key(after_dot)?;
+        key = format!("{}.{}", key, part);
+        rest = remaining;
+    }
+    Ok((key, rest))
+}
+
+fn parse_value(s: &str) -> Result<(Value, &str), String> {
+    let s = s.trim_start();
+    if s.starts_with(['"', '\'']) {
+        let (string, rest) = parse_string(s)?;
+        return Ok((Value::String(string), rest));
+    }
+    let end = s
+        .find(|c: char| c.is_whitespace() || c == '#')
+        .unwrap_or(s.len());
+    let (token, rest) = s.split_at(end);
+    let value = match token {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => {
+            let number = token.replace('_', "");
+            if let Ok(n) = number.parse::<i64>() {
+                Value::Integer(n)
+            } else if let Ok(x) = number.parse::<f64>() {
+                Value::Float(x)
+            } else {
+                return Err(format!("unsupported value {}", token));
+            }
+        }
+    };
+    Ok((value, rest))
+}
+
+fn expect_end(rest: &str) -> Result<(), String> {
+    let rest = rest.trim_start();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected {}", rest))
+    }
+}
+
+pub fn parse(text: &str) -> Result<Document, String> {
+    let mut ret = Document::new();
+    let mut table = String::new();
+    ret.insert(table.clone(), BTreeMap::new());
+    for (index, line) in text.lines().enumerate() {
+        let error = |message: String| format!("line {}: {}", index + 1, message);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let (name, rest) = parse_dotted_key(header).map_err(error)?;
+            let rest = rest
+                .trim_start()
+                .strip_prefix(']')
+                .ok_or_else(|| error(String::from("expected ]")))?;
+            expect_end(rest).map_err(error)?;
+            if ret.contains_key(&name) {
+                return Err(error(format!("table {} defined twice", name)));
+            }
+            ret.insert(name.clone(), BTreeMap::new());
+            table = name;
+            continue;
+        }
+        let (key, rest) = parse_dotted_key(line).map_err(error)?;
+        let rest = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or_else(|| error(String::from("expected =")))?;
+        let (value, rest) = parse_value(rest).map_err(error)?;
+        expect_end(rest).map_err(error)?;
+        if ret
+            .get_mut(&table)
+            .unwrap()
+            .insert(key.clone(), value)
+            .is_some()
+        {
+            return Err(error(format!("key {} defined twice", key)));
+        }
+    }
+    Ok(ret)
+}
@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::prelude::*;
+
+use crate::aoc::json::{self, Value};
+use crate::config::Config;
+use crate::solution::{InputSource, PartRun};
+
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// One part solved in one run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub timestamp: DateTime<Utc>,
+    /// Commit the runner was built from, if it was built inside a checkout.
+    pub commit: Option<String>,
+    pub day: u32,
+    pub part: u32,
+    pub input_hash: String,
+    pub answer: String,
+    pub duration: Duration,
+    pub implementation: String,
+}
+
+impl Record {
+    fn to_json(&self) -> Value {
+        let members = [
+            ("timestamp", Value::String(self.timestamp.to_rfc3339())),
+            (
+                "commit",
+                self.commit.clone().map_or(Value::Null, Value::String),
+            ),
+            ("day", Value::Number(self.day as f64)),
+            ("part", Value::Number(self.part as f64)),
+            ("input_hash", Value::String(self.input_hash.clone())),
+            ("answer", Value::String(self.answer.clone())),
+            (
+                "duration_ns",
+                Value::Number(self.duration.as_nanos() as f64),
+            ),
+            ("implementation", Value::String(self.implementation.clone())),
+        ];
+        Value::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (String::from(key), value))
+                .collect(),
+        )
+    }
+    fn from_json(value: &Value) -> Option<Record> {
+        let string = |key: &str| value.get(key)?.as_str().map(String::from);
+        Some(Record {
+            timestamp: DateTime::parse_from_rfc3339(&string("timestamp")?)
+                .ok()?
+                .with_timezone(&Utc),
+            commit: string("commit"),
+            day: value.get("day")?.as_u64()? as u32,
+            part: value.get("part")?.as_u64()? as u32,
+            input_hash: string("input_hash")?,
+            answer: string("answer")?,
+            duration: Duration::from_nanos(value.get("duration_ns")?.as_u64()?),
+            implementation: string("implementation")?,
+        })
+    }
+}
+
+/// 64-bit FNV-1a of the input, which stays the same across Rust versions
+/// unlike the standard library's hashers. The input is read a buffer at a
+/// time, so it never has to fit in memory.
+pub fn hash_input(mut input: impl BufRead) -> io::Result<String> {
+    let mut hash = 0xcbf29ce484222325u64;
+    loop {
+        let chunk = input.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        hash = chunk.iter().fold(hash, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        });
+        let len = chunk.len();
+        input.consume(len);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// Short hash of the commit the runner was built from, which `build.rs`
+/// looks up, if it was built inside a checkout.
+pub fn current_commit() -> Option<String> {
+    option_env!("AOC_BUILD_COMMIT").map(String::from)
+}
+
+/// Append-only log of every run, one JSON record per line.
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: impl AsRef<Path>) -> History {
+        History {
+            path: path.as_ref().to_owned(),
+        }
+    }
+    pub fn open(config: &Config) -> History {
+        History::new(config.cache_dir.join(HISTORY_FILE))
+    }
+    pub fn append(&self, records: &[Record]) -> io::Result<()> {
+        let lines = records
+            .iter()
+            .map(|record| format!("{}\n", record.to_json()))
+            .collect::<String>();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // A single write keeps the records of a run together.
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(lines.as_bytes())
+    }
+    /// Every record in the order they were made. Lines that do not hold a
+    /// record, e.g. one cut short by a crash, are skipped with a warning.
+    pub fn read(&self) -> io::Result<Vec<Record>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut ret = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match json::parse(line).ok().as_ref().and_then(Record::from_json) {
+                Some(record) => ret.push(record),
+                None => eprintln!("Skipping line {} of {}", index + 1, self.path.display()),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// Records the answers `runs` of `implementation` for `day` on `source`.
+pub fn record(
+    history: &History,
+    day: u32,
+    implementation: &str,
+    source: &InputSource,
+    runs: &[PartRun],
+) -> io::Result<()> {
+    let input_hash = hash_input(BufReader::new(File::open(source.path())?))?;
+    let commit = current_commit();
+    let timestamp = Utc::now();
+    let records = runs
+        .iter()
+        .zip(1..)
+        .map(|(run, part)| Record {
+            timestamp,
+            commit: commit.clone(),
+            day,
+            part,
+            input_hash: input_hash.clone(),
+            answer: run.answer.clone(),
+            duration: run.duration,
+            implementation: implementation.to_owned(),
+        })
+        .collect::<Vec<_>>();
+    history.append(&records)
+}
+
+/// For each record, the different answer given last time for the same part
+/// of the same input, if the answer changed.
+pub fn answer_changes(records: &[Record]) -> Vec<Option<&str>> {
+    let mut last_answers = HashMap::new();
+    records
+        .iter()
+        .map(|record| {
+            let key = (record.day, record.part, record.input_hash.as_str());
+            let previous = last_answers.insert(key, record.answer.as_str());
+            previous.filter(|previous| *previous != record.answer)
+        })
+        .collect()
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Runs of `day` oldest first, with answer changes flagged, as table rows
+/// starting with a header.
+pub fn run_rows(records: &[Record], day: u32) -> Vec<Vec<String>> {
+    let header = [
+        "When",
+        "Commit",
+        "Implementation",
+        "Part",
+        "Input",
+        "Answer",
+        "Time",
+        "Note",
+    ];
+    let mut ret = vec![header.iter().map(|cell| cell.to_string()).collect()];
+    for (record, change) in records.iter().zip(answer_changes(records)) {
+        if record.day != day {
+            continue;
+        }
+        ret.push(vec![
+            record.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            record.commit.clone().unwrap_or_default(),
+            record.implementation.clone(),
+            record.part.to_string(),
+            record.input_hash.chars().take(8).collect(),
+            record.answer.clone(),
+            format_duration(record.duration),
+            change.map_or_else(String::new, |previous| format!("changed from {}", previous)),
+        ]);
+    }
+    ret
+}
+
+/// How the time taken for `day` evolved: one row per commit and part, in
+/// the order the commits were first run, with the best and median time of
+/// all runs on that commit.
+pub fn trend_rows(records: &[Record], day: u32) -> Vec<Vec<String>> {
+    let header = ["Commit", "First run", "Part", "Runs", "Best", "Median"];
+    let mut commits = Vec::new();
+    let mut durations = BTreeMap::<(usize, u32), Vec<Duration>>::new();
+    for record in records.iter().filter(|record| record.day == day) {
+        let commit = record.commit.as_deref().unwrap_or("");
+        let index = match commits.iter().position(|(c, _)| *c == commit) {
+            Some(index) => index,
+            None => {
+                commits.push((commit, record.timestamp));
+                commits.len() - 1
+            }
+        };
+        durations
+            .entry((index, record.part))
+            .or_default()
+            .push(record.duration);
+    }
+    let mut ret = vec![header.iter().map(|cell| cell.to_string()).collect()];
+    for ((index, part), mut times) in durations {
+        times.sort();
+        let (commit, first_run) = commits[index];
+        ret.push(vec![
+            commit.to_owned(),
+            first_run.format("%Y-%m-%d %H:%M:%S").to_string(),
+            part.to_string(),
+            times.len().to_string(),
+            format_duration(times[0]),
+            format_duration(times[times.len() / 2]),
+        ]);
+    }
+    ret
+}
@@ -0,0 +1,97 @@
+use std::env;
+use std::fs;
+use std::io::BufReader;
+use std::process;
+use std::time::Duration;
+
+use chrono::prelude::*;
+
+use super::*;
+
+fn record(minute: u32, commit: &str, part: u32, input: &str, answer: &str, millis: u64) -> Record {
+    Record {
+        timestamp: Utc.with_ymd_and_hms(2023, 12, 1, 5, minute, 0).unwrap(),
+        commit: Some(commit.to_owned()).filter(|commit| !commit.is_empty()),
+        day: 1,
+        part,
+        input_hash: hash_input(input.as_bytes()).unwrap(),
+        answer: answer.to_owned(),
+        duration: Duration::from_millis(millis),
+        implementation: String::from("default"),
+    }
+}
+
+#[test]
+fn hashes_inputs_stably() {
+    assert_eq!(hash_input(&b""[..]).unwrap(), "cbf29ce484222325");
+    assert_eq!(hash_input(&b"a"[..]).unwrap(), "af63dc4c8601ec8c");
+    // The hash does not depend on how the input is split into buffers.
+    let input = "1abc2\npqr3stu8vwx\n";
+    assert_eq!(
+        hash_input(BufReader::with_capacity(3, input.as_bytes())).unwrap(),
+        hash_input(input.as_bytes()).unwrap()
+    );
+}
+
+#[test]
+fn appends_and_reads_back_records() {
+    let path = env::temp_dir()
+        .join(format!("aoc2023-test-{}-history", process::id()))
+        .join(HISTORY_FILE);
+    let history = History::new(&path);
+    assert!(history.read().unwrap().is_empty());
+    let records = [
+        record(0, "abc1234", 1, "input", "142", 3),
+        record(0, "", 2, "input", "line\n\"two\"", 5),
+    ];
+    history.append(&records[..1]).unwrap();
+    // A line cut short is skipped rather than losing the whole history.
+    fs::write(
+        &path,
+        fs::read_to_string(&path).unwrap() + "{\"timestamp\": \"2023\n",
+    )
+    .unwrap();
+    history.append(&records[1..]).unwrap();
+    assert_eq!(history.read().unwrap(), records);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn flags_changed_answers_for_the_same_input() {
+    let records = [
+        record(0, "a", 1, "input", "142", 3),
+        record(1, "a", 1, "other", "77", 3),
+        record(2, "b", 1, "input", "143", 3),
+        record(3, "b", 2, "input", "281", 3),
+        record(4, "c", 1, "input", "143", 3),
+    ];
+    assert_eq!(
+        answer_changes(&records),
+        [None, None, Some("142"), None, None]
+    );
+    let rows = run_rows(&records, 1);
+    assert_eq!(rows.len(), 6);
+    assert_eq!(rows[3][7], "changed from 142");
+    assert_eq!(rows[3][..2], ["2023-12-01 05:02:00", "b"]);
+    assert_eq!(run_rows(&records, 2).len(), 1);
+}
+
+#[test]
+fn summarizes_timings_per_commit() {
+    let records = [
+        record(0, "b", 1, "input", "142", 30),
+        record(1, "a", 1, "input", "142", 10),
+        record(2, "b", 1, "input", "142", 20),
+        record(3, "b", 2, "input", "281", 7),
+        record(4, "b", 1, "input", "142", 40),
+    ];
+    assert_eq!(
+        trend_rows(&records, 1),
+        [
+            vec!["Commit", "First run", "Part", "Runs", "Best", "Median"],
+            vec!["b", "2023-12-01 05:00:00", "1", "3", "20.000ms", "30.000ms"],
+            vec!["b", "2023-12-01 05:00:00", "2", "1", "7.000ms", "7.000ms"],
+            vec!["a", "2023-12-01 05:01:00", "1", "1", "10.000ms", "10.000ms"],
+        ]
+    );
+}
@@ -2,71 +2,351 @@
 extern crate prettytable;
 use chrono::prelude::*;
 use dotenv::dotenv;
-use macros::{today, today_result};
-use prettytable::Table;
-use reqwest::blocking::Client;
-use reqwest::{header, Method};
-use solution::Solution;
+use prettytable::{Cell, Row, Table};
+use solution::profile::CountingAllocator;
+use solution::{InputSource, PartRun, RunOptions};
+use std::env;
 use std::error::Error;
-use std::{env, fs};
+use std::io;
+use std::net::TcpListener;
+use std::path::Path;
+use std::time::SystemTime;
 
+use aoc::race::{self, Clock, SystemClock};
+use aoc::store::DayStore;
+use config::{Config, OutputFormat};
+use history::History;
+use utils::trace::{self, Level};
+use utils::{animate, render};
+
+mod aoc;
+mod config;
+mod history;
+mod serve;
 mod solution;
+mod utils;
+
+// Only counts while profiling with `--profile`.
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn get_current_day() -> u32 {
-    let t = Utc::now();
-    let tz_offset = FixedOffset::west_opt(5 * 3600).unwrap();
-    tz_offset.from_utc_datetime(&t.naive_utc()).day()
-}
-
-fn run_sample_cases() -> Result<today_result!(), Box<dyn Error>> {
-    let sample_input_data = fs::read_to_string("sample.txt")?;
-    Ok((
-        <today!()>::part_one(sample_input_data.lines()),
-        <today!()>::part_two(sample_input_data.lines()),
-    ))
-}
-
-fn run_main_case() -> Result<today_result!(), Box<dyn Error>> {
-    let today = get_current_day();
-    let client = Client::new();
-    let login_session =
-        env::var("LOGIN_SESSION").unwrap_or_else(|_| panic!("Cannot find LOGIN_SESSION"));
-    match client
-        .request(
-            Method::GET,
-            format!("https://adventofcode.com/2023/day/{}/input", today),
+    race::puzzle_day(Utc::now())
+}
+
+/// Runs the configured implementation for `day` on `source` and records
+/// the run in the history. `--profile` counts the allocations of each part,
+/// `--stats` collects the counters solutions keep of their work, and
+/// `--render <dir>` saves the pictures solutions draw into `<dir>/<case>`.
+/// Simulations are played in the terminal with `--animate [fps]`, or their
+/// frames dumped to `<dir>/<case>.txt` with `--frames <dir>`.
+fn run_cases(
+    config: &Config,
+    day: u32,
+    case: &str,
+    source: &InputSource,
+) -> Result<[PartRun; 2], Box<dyn Error>> {
+    let implementation = config.implementation(day);
+    render::set_output_dir(flag_value("--render").map(|dir| Path::new(&dir).join(case)));
+    let options = RunOptions {
+        profile_allocations: has_flag("--profile"),
+        collect_counters: has_flag("--stats"),
+    };
+    if let Some(dir) = flag_value("--frames") {
+        animate::dump(&Path::new(&dir).join(format!("{}.txt", case)))?;
+    } else if has_flag("--animate") {
+        let fps = flag_value("--animate").and_then(|fps| fps.parse().ok());
+        animate::play(fps.unwrap_or(10));
+    }
+    let runs = solution::run_day(day, implementation, source, options);
+    animate::stop()?;
+    let runs = runs.ok_or_else(|| {
+        format!(
+            "There is no {} implementation for day {} yet",
+            implementation, day
         )
-        .header(header::COOKIE, format!("session={}", login_session))
-        .send()
-    {
-        Ok(res) => {
-            let input_data = res.text()?;
-            Ok((
-                <today!()>::part_one(input_data.lines()),
-                <today!()>::part_two(input_data.lines()),
-            ))
-        }
-        Err(err) => {
+    })??;
+    let history = History::open(config);
+    if let Err(err) = history::record(&history, day, implementation, source, &runs) {
+        eprintln!("Could not record the run in the history: {}", err);
+    }
+    Ok(runs)
+}
+
+/// Runs on the first example of the day, or on `sample.txt` before the
+/// examples have been fetched.
+fn run_sample_cases(config: &Config, day: u32) -> Result<[PartRun; 2], Box<dyn Error>> {
+    let sample = config.day_store(day).example_paths()?.into_iter().next();
+    let source = InputSource::file(sample.unwrap_or_else(|| "sample.txt".into()));
+    run_cases(config, day, "sample", &source)
+}
+
+/// Downloads the input for `day` into its day store, unless it is already
+/// there.
+fn download_main_input(config: &Config, store: &DayStore, day: u32) -> Result<(), Box<dyn Error>> {
+    if store.input_path().exists() {
+        return Ok(());
+    }
+    store
+        .download_input(
+            &config.client()?,
+            &format!("{}/input", config.puzzle_url(day)),
+        )
+        .map_err(|err| {
             eprintln!("Error getting input for today: {:#?}", err);
-            Err(Box::new(err))
+            err
+        })
+}
+
+fn run_main_case(config: &Config, day: u32) -> Result<[PartRun; 2], Box<dyn Error>> {
+    let store = config.day_store(day);
+    download_main_input(config, &store, day)?;
+    run_cases(config, day, "main", &InputSource::file(store.input_path()))
+}
+
+/// The answer to a part, with what it allocated when profiling on the next
+/// line or in parentheses.
+fn format_part(run: &PartRun, multiline: bool) -> String {
+    match run.allocations {
+        Some(stats) if multiline => format!("{}\n{}", run.answer, stats),
+        Some(stats) => format!("{} ({})", run.answer, stats),
+        None => run.answer.clone(),
+    }
+}
+
+/// Every counter of both parts of the runs in `rows`, as rows of input name
+/// and counter with the count for each part, `-` if not counted.
+fn counter_rows(rows: &[(String, [PartRun; 2])]) -> Vec<(String, [String; 2])> {
+    let mut ret = Vec::new();
+    for (name, parts) in rows {
+        let counts = parts
+            .each_ref()
+            .map(|run| run.counters.clone().unwrap_or_default());
+        let mut counters = counts.iter().flat_map(|c| c.keys()).collect::<Vec<_>>();
+        counters.sort();
+        counters.dedup();
+        for counter in counters {
+            let values = counts.each_ref().map(|c| {
+                c.get(counter)
+                    .map_or_else(|| String::from("-"), u64::to_string)
+            });
+            ret.push((format!("{} {}", name, counter), values));
+        }
+    }
+    ret
+}
+
+/// Prints the answers for `day`, one row per input, in the configured output
+/// format, followed by what the solution counted if collecting counters.
+fn print_results(config: &Config, day: u32, rows: &[(String, [PartRun; 2])]) {
+    let counters = counter_rows(rows);
+    match config.output {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row![format!("d = {}", day), "Part 1", "Part 2"]);
+            for (name, [p1, p2]) in rows {
+                table.add_row(row![name, format_part(p1, true), format_part(p2, true)]);
+            }
+            table.printstd();
+            if !counters.is_empty() {
+                let mut table = Table::new();
+                table.add_row(row!["Counter", "Part 1", "Part 2"]);
+                for (counter, [c1, c2]) in counters {
+                    table.add_row(row![counter, r->c1, r->c2]);
+                }
+                table.printstd();
+            }
+        }
+        OutputFormat::Plain => {
+            for (name, [p1, p2]) in rows {
+                println!(
+                    "Day {} {}: {} {}",
+                    day,
+                    name,
+                    format_part(p1, false),
+                    format_part(p2, false)
+                );
+            }
+            for (counter, [c1, c2]) in counters {
+                println!("Day {} {}: {} {}", day, counter, c1, c2);
+            }
+        }
+    }
+}
+
+/// Value following `flag` on the command line, e.g. `--input <path>`.
+fn flag_value(flag: &str) -> Option<String> {
+    env::args().skip_while(|arg| arg != flag).nth(1)
+}
+
+fn has_flag(flag: &str) -> bool {
+    env::args().any(|arg| arg == flag)
+}
+
+/// `describe --day N [--refresh]`: prints the puzzle description as Markdown.
+fn describe(config: &Config) -> Result<(), Box<dyn Error>> {
+    let day = match flag_value("--day") {
+        Some(day) => day.parse::<u32>()?,
+        None => get_current_day(),
+    };
+    let markdown = aoc::describe::describe(config, &config.client()?, day, has_flag("--refresh"))?;
+    print!("{}", markdown);
+    Ok(())
+}
+
+/// `leaderboard --id N`: prints a private leaderboard.
+fn leaderboard(config: &Config) -> Result<(), Box<dyn Error>> {
+    let id = flag_value("--id")
+        .ok_or("leaderboard needs --id <leaderboard id>")?
+        .parse::<u64>()?;
+    let leaderboard = aoc::leaderboard::load(config, &config.client()?, id, SystemTime::now())?;
+    leaderboard.to_table().printstd();
+    Ok(())
+}
+
+/// `race [--day N [--year Y]]`: waits for the puzzle to unlock, defaulting
+/// to the next one, then fetches it and runs the sample and main input
+/// straight away.
+fn race(config: &Config) -> Result<(), Box<dyn Error>> {
+    let clock = SystemClock;
+    let mut config = config.clone();
+    let day = match flag_value("--day") {
+        Some(day) => day.parse::<u32>()?,
+        None => {
+            let (year, day) = race::next_unlock(clock.now());
+            config.year = year;
+            day
         }
+    };
+    config.session()?;
+    race::wait_until(&clock, race::unlock_time(config.year, day), |remaining| {
+        eprint!(
+            "\rDay {} unlocks in {}  ",
+            day,
+            race::format_countdown(remaining)
+        );
+    });
+    eprintln!("\rDay {} is unlocked, fetching input", day);
+    let store = config.day_store(day);
+    let input_url = format!("{}/input", config.puzzle_url(day));
+    // The input is retried here until it unlocks, so each fetch is sent once.
+    let client = config.client()?.with_attempts(1);
+    race::with_backoff(&clock, race::FETCH_ATTEMPTS, || {
+        store.download_input(&client, &input_url)
+    })?;
+    // The examples are nice to have but must not hold up the main input.
+    if let Err(err) = aoc::describe::describe(&config, &client, day, true) {
+        eprintln!("Could not fetch the examples for day {}: {}", day, err);
+    }
+    let sample = store.example_paths()?.into_iter().next();
+    let cases = sample
+        .map(|path| ("Sample input", "sample", path))
+        .into_iter()
+        .chain([("Main input", "main", store.input_path())]);
+    let mut rows = Vec::new();
+    for (name, case, path) in cases {
+        let runs = run_cases(&config, day, case, &InputSource::file(path))?;
+        rows.push((String::from(name), runs));
+    }
+    print_results(&config, day, &rows);
+    Ok(())
+}
+
+/// `serve [--port N]`: answers puzzle inputs sent over HTTP on localhost
+/// until stopped.
+fn serve(config: &Config) -> Result<(), Box<dyn Error>> {
+    let port = match flag_value("--port") {
+        Some(port) => port.parse::<u16>()?,
+        None => serve::DEFAULT_PORT,
+    };
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    eprintln!("Serving the solvers on http://{}", listener.local_addr()?);
+    serve::serve(config, &listener);
+    Ok(())
+}
+
+/// `history [--day N] [--trend] [--csv]`: prints the recorded runs of a day,
+/// or with `--trend` how its timings evolved, as a table or CSV.
+fn show_history(config: &Config) -> Result<(), Box<dyn Error>> {
+    let day = match flag_value("--day") {
+        Some(day) => day.parse::<u32>()?,
+        None => get_current_day(),
+    };
+    let records = History::open(config).read()?;
+    let rows = if has_flag("--trend") {
+        history::trend_rows(&records, day)
+    } else {
+        history::run_rows(&records, day)
+    };
+    let mut table = Table::new();
+    for row in rows {
+        table.add_row(Row::new(row.iter().map(|cell| Cell::new(cell)).collect()));
+    }
+    if has_flag("--csv") {
+        table.to_csv(io::stdout())?;
+    } else {
+        table.printstd();
+    }
+    Ok(())
+}
+
+/// `config show`: prints the effective configuration.
+fn show_config(config: &Config) -> Result<(), Box<dyn Error>> {
+    match env::args().nth(2).as_deref() {
+        Some("show") => {
+            print!("{}", config.show());
+            Ok(())
+        }
+        _ => Err("Usage: config show".into()),
     }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     dotenv().ok();
-    let mut table = Table::new();
-    let (sample_p1, sample_p2) = run_sample_cases()?;
-    let (main_p1, main_p2) = run_main_case()?;
+    let args = env::args().collect::<Vec<_>>();
+    let config = Config::load(&args, |var| env::var(var).ok())?;
+    match args.get(1).map(String::as_str) {
+        Some("describe") => return describe(&config),
+        Some("leaderboard") => return leaderboard(&config),
+        Some("race") => return race(&config),
+        Some("config") => return show_config(&config),
+        Some("history") => return show_history(&config),
+        Some("serve") => return serve(&config),
+        _ => {}
+    }
+    // `--day N` runs another day than today's.
+    let day = match flag_value("--day") {
+        Some(day) => day.parse::<u32>()?,
+        None => get_current_day(),
+    };
+    // `--explain [level]` shows what the solution traces, from `debug` on by
+    // default.
+    if has_flag("--explain") {
+        let level = flag_value("--explain").and_then(|level| Level::parse(&level));
+        trace::set_max_level(Some(level.unwrap_or(Level::Debug)));
+    }
+    // `--input <path>` runs on a given input instead, where `-` is stdin.
+    if let Some(path) = flag_value("--input") {
+        let source = if path == "-" {
+            InputSource::spool(io::stdin().lock(), "stdin")?
+        } else {
+            InputSource::file(&path)
+        };
+        let runs = run_cases(&config, day, "input", &source)?;
+        print_results(&config, day, &[(path, runs)]);
+        return Ok(());
+    }
+    let sample_runs = run_sample_cases(&config, day)?;
+    let main_runs = run_main_case(&config, day)?;
     println!("");
-    table.add_row(row![
-        format!("d = {}", get_current_day()),
-        "Part 1",
-        "Part 2"
-    ]);
-    table.add_row(row!["Sample input", sample_p1, sample_p2]);
-    table.add_row(row!["Main input", main_p1, main_p2]);
-    table.printstd();
+    print_results(
+        &config,
+        day,
+        &[
+            (String::from("Sample input"), sample_runs),
+            (String::from("Main input"), main_runs),
+        ],
+    );
     println!("");
     Ok(())
 }
@@ -0,0 +1,273 @@
+//! The solvers over HTTP on localhost, for tools that want answers without
+//! running the CLI:
+//!
+//! - `GET /days` lists the days with a solution and their answer types.
+//! - `POST /2023/day/{n}/part/{p}` solves a part for the input in the body.
+//!
+//! Both answer with JSON. Requests are handled one at a time.
+
+#[cfg(test)]
+mod tests;
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use crate::aoc::json::Value;
+use crate::aoc::YEAR;
+use crate::config::Config;
+use crate::solution::{self, RunOptions};
+
+pub const DEFAULT_PORT: u16 = 2023;
+
+/// Largest input accepted, far above any puzzle input.
+pub const MAX_BODY_BYTES: usize = 16 << 20;
+
+/// How long a client may take to send its request.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string.
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+fn object<'a>(members: impl IntoIterator<Item = (&'a str, Value)>) -> Value {
+    Value::Object(
+        members
+            .into_iter()
+            .map(|(key, value)| (String::from(key), value))
+            .collect(),
+    )
+}
+
+impl Response {
+    fn error(status: u16, message: String) -> Response {
+        Response {
+            status,
+            body: object([("error", Value::String(message))]),
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        _ => "",
+    }
+}
+
+/// Reads an HTTP/1.1 request. Bodies need a `Content-Length`. Requests that
+/// cannot be read are answered with the error response returned.
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let bad_request = |message: String| Response::error(400, message);
+    let mut read_line = || {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => Err(bad_request(String::from("Request ended early"))),
+            Ok(_) => Ok(line.trim_end().to_owned()),
+            Err(err) => Err(bad_request(err.to_string())),
+        }
+    };
+    let request_line = read_line()?;
+    let (method, target) = match request_line.split(' ').collect::<Vec<_>>()[..] {
+        [method, target, version] if version.starts_with("HTTP/") => (method, target),
+        _ => {
+            return Err(bad_request(format!(
+                "Malformed request line {:?}",
+                request_line
+            )))
+        }
+    };
+    let mut content_length = 0;
+    loop {
+        let header = read_line()?;
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request(format!("Malformed header {:?}", header)));
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .trim()
+                .parse()
+                .map_err(|_| bad_request(format!("Invalid Content-Length {}", value.trim())))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(bad_request(String::from(
+                "Chunked bodies are not supported, send a Content-Length",
+            )));
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(Response::error(
+            413,
+            format!("Bodies are limited to {} bytes", MAX_BODY_BYTES),
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| bad_request(err.to_string()))?;
+    Ok(Request {
+        method: method.to_owned(),
+        path: target.split('?').next().unwrap().to_owned(),
+        body,
+    })
+}
+
+pub fn write_response(writer: &mut impl Write, response: &Response) -> io::Result<()> {
+    let body = response.body.to_string();
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+/// Answers `request` with the solvers `config` picks.
+pub fn handle(config: &Config, request: &Request) -> Response {
+    let segments = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+    match (request.method.as_str(), &segments[..]) {
+        ("GET", ["days"]) => list_days(),
+        ("POST", [year, "day", day, "part", part]) => solve(config, year, day, part, &request.body),
+        (_, ["days"]) | (_, [_, "day", _, "part", _]) => Response::error(
+            405,
+            format!("{} is not allowed on {}", request.method, request.path),
+        ),
+        _ => Response::error(404, format!("Nothing at {}", request.path)),
+    }
+}
+
+fn list_days() -> Response {
+    let days = solution::registered_days()
+        .into_iter()
+        .map(|registered| {
+            let answer_types = registered
+                .answer_types
+                .iter()
+                .map(|name| Value::String(name.to_string()))
+                .collect();
+            object([
+                ("day", Value::Number(registered.day as f64)),
+                ("answer_types", Value::Array(answer_types)),
+            ])
+        })
+        .collect();
+    Response {
+        status: 200,
+        body: object([("days", Value::Array(days))]),
+    }
+}
+
+/// Message of a panic, which is how solutions report malformed input.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => String::from("The solution panicked"),
+    }
+}
+
+fn solve(config: &Config, year: &str, day: &str, part: &str, body: &[u8]) -> Response {
+    let (Ok(year), Ok(day), Ok(part)) = (year.parse::<u32>(), day.parse::<u32>(), part.parse())
+    else {
+        return Response::error(
+            404,
+            format!("Nothing at /{}/day/{}/part/{}", year, day, part),
+        );
+    };
+    if year != YEAR {
+        return Response::error(404, format!("Only {} puzzles are solved here", YEAR));
+    }
+    if !(1..=2).contains(&part) {
+        return Response::error(404, format!("Day {} has no part {}", day, part));
+    }
+    let Ok(text) = std::str::from_utf8(body) else {
+        return Response::error(400, String::from("The input is not UTF-8"));
+    };
+    let implementation = config.implementation(day);
+    let run = panic::catch_unwind(AssertUnwindSafe(|| {
+        solution::run_day_part(day, implementation, part, text, RunOptions::default())
+    }));
+    let members = [
+        ("day", Value::Number(day as f64)),
+        ("part", Value::Number(part as f64)),
+    ];
+    match run {
+        Ok(Some(run)) => Response {
+            status: 200,
+            body: object(members.into_iter().chain([
+                ("answer", Value::String(run.answer)),
+                ("duration_ns", Value::Number(run.duration.as_nanos() as f64)),
+                ("error", Value::Null),
+            ])),
+        },
+        Ok(None) => Response::error(
+            404,
+            format!(
+                "There is no {} implementation for day {} yet",
+                implementation, day
+            ),
+        ),
+        Err(payload) => Response {
+            status: 422,
+            body: object(members.into_iter().chain([
+                ("answer", Value::Null),
+                ("duration_ns", Value::Null),
+                ("error", Value::String(panic_message(&*payload))),
+            ])),
+        },
+    }
+}
+
+fn handle_connection(config: &Config, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let response = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => {
+            let response = handle(config, &request);
+            eprintln!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+    write_response(&mut &stream, &response)
+}
+
+/// Answers requests on `listener` one at a time, forever.
+pub fn serve(config: &Config, listener: &TcpListener) {
+    for stream in listener.incoming() {
+        let answered = stream.and_then(|stream| handle_connection(config, stream));
+        if let Err(err) = answered {
+            eprintln!("Could not answer a request: {}", err);
+        }
+    }
+}
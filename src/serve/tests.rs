@@ -0,0 +1,114 @@
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use super::*;
+use crate::aoc::json;
+
+const DAY7_SAMPLE: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day7.txt"));
+
+fn request(method: &str, path: &str, body: &str) -> Request {
+    Request {
+        method: String::from(method),
+        path: String::from(path),
+        body: body.as_bytes().to_vec(),
+    }
+}
+
+#[test]
+fn lists_registered_days() {
+    let response = handle(&Config::default(), &request("GET", "/days", ""));
+    assert_eq!(response.status, 200);
+    let days = match response.body.get("days") {
+        Some(Value::Array(days)) => days,
+        other => panic!("Expected a list of days, got {:?}", other),
+    };
+    assert_eq!(days.len(), 23);
+    let day7 = days
+        .iter()
+        .find(|day| day.get("day").and_then(Value::as_u64) == Some(7))
+        .unwrap();
+    assert_eq!(
+        day7.get("answer_types").unwrap().to_string(),
+        r#"["u32","u32"]"#
+    );
+}
+
+#[test]
+fn solves_posted_inputs() {
+    let config = Config::default();
+    let response = handle(&config, &request("POST", "/2023/day/7/part/2", DAY7_SAMPLE));
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        response.body.get("answer").and_then(Value::as_str),
+        Some("5905")
+    );
+    assert!(response
+        .body
+        .get("duration_ns")
+        .and_then(Value::as_u64)
+        .is_some());
+    assert_eq!(response.body.get("error"), Some(&Value::Null));
+
+    let malformed = handle(&config, &request("POST", "/2023/day/7/part/1", "AAAAA x\n"));
+    assert_eq!(malformed.status, 422);
+    assert_eq!(malformed.body.get("answer"), Some(&Value::Null));
+    assert_eq!(
+        malformed.body.get("error").and_then(Value::as_str),
+        Some("Invalid hand on line 1: AAAAA x")
+    );
+
+    let statuses = [
+        ("POST", "/2023/day/7/part/3"),
+        ("POST", "/2023/day/26/part/1"),
+        ("POST", "/2022/day/7/part/1"),
+        ("GET", "/2023/day/7/part/1"),
+        ("POST", "/days"),
+        ("GET", "/"),
+    ]
+    .map(|(method, path)| handle(&config, &request(method, path, DAY7_SAMPLE)).status);
+    assert_eq!(statuses, [404, 404, 404, 405, 405, 404]);
+}
+
+#[test]
+fn reads_requests_with_a_body() {
+    let raw = "POST /2023/day/7/part/1?verbose HTTP/1.1\r\nHost: localhost\r\n\
+               content-length: 5\r\n\r\nhello and more";
+    let parsed = read_request(&mut raw.as_bytes()).unwrap();
+    assert_eq!(parsed, request("POST", "/2023/day/7/part/1", "hello"));
+
+    let chunked = "POST /days HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n";
+    assert_eq!(
+        read_request(&mut chunked.as_bytes()).unwrap_err().status,
+        400
+    );
+    let huge = format!(
+        "POST /days HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+        MAX_BODY_BYTES + 1
+    );
+    assert_eq!(read_request(&mut huge.as_bytes()).unwrap_err().status, 413);
+}
+
+#[test]
+fn answers_clients_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(&Config::default(), &listener));
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "POST /2023/day/7/part/1 HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+        DAY7_SAMPLE.len(),
+        DAY7_SAMPLE
+    )
+    .unwrap();
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_to_string(&mut response)
+        .unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head);
+    let body = json::parse(body).unwrap();
+    assert_eq!(body.get("answer").and_then(Value::as_str), Some("6440"));
+}
@@ -1,95 +1,111 @@
 use macros::return_type;
 
-use crate::solution::Solution;
-use std::collections::HashMap;
+use crate::solution::{Input, LineStream, Solution};
+use crate::utils::trace::{event, Level};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day1;
 
-impl Solution<u32, u32> for Day1 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut total = 0;
-        for line in lines {
-            let mut first_digit: Option<u32>= None;
-            let mut last_digit: Option<u32> = None;
-            for c in line.chars() {
-                if c.is_digit(10) {
-                    if let None = first_digit {
-                        first_digit = c.to_digit(10);
-                    }
-                    last_digit = c.to_digit(10);
+const SPELLED_DIGITS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+impl Day1 {
+    fn calibration_value(line: &str) -> Option<u32> {
+        let mut first_digit: Option<u32> = None;
+        let mut last_digit: Option<u32> = None;
+        for c in line.chars() {
+            if c.is_ascii_digit() {
+                if first_digit.is_none() {
+                    first_digit = c.to_digit(10);
                 }
+                last_digit = c.to_digit(10);
             }
-            if let None = first_digit { 
-                continue; 
-            }
-            total += first_digit.unwrap() * 10 + last_digit.unwrap();
         }
-        total
+        Some(first_digit? * 10 + last_digit?)
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut total = 0;
-        let num_map: HashMap<&'static str, u32> = HashMap::from([
-            ("one", 1),
-            ("two", 2),
-            ("three", 3),
-            ("four", 4),
-            ("five", 5),
-            ("six", 6),
-            ("seven", 7),
-            ("eight", 8), 
-            ("nine", 9)
-        ]);
-        for line in lines {
-            let c_list: Vec<char> = line.chars().collect();
-            let mut i = 0;
-            let mut first_digit: Option<u32> = None;
-            let mut last_digit: Option<u32> = None;
-            while i < c_list.len() {
-                if c_list[i].is_digit(10) {
-                    first_digit = c_list[i].to_digit(10);
-                    break;
-                }
-                let mut found = false;
-                for (k, v) in &num_map {
-                    if i+k.len() <= c_list.len() && String::from(*k) == String::from(&line[i..i+k.len()]) {
-                        first_digit = Some(*v);
-                        found = true;
-                        break;
-                    }
-                }
-                if found {
+    fn spelled_calibration_value(line: &str) -> Option<u32> {
+        let c_list: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        let mut first_digit: Option<u32> = None;
+        let mut last_digit: Option<u32> = None;
+        while i < c_list.len() {
+            if c_list[i].is_ascii_digit() {
+                first_digit = c_list[i].to_digit(10);
+                break;
+            }
+            let mut found = false;
+            for (k, v) in &SPELLED_DIGITS {
+                if i + k.len() <= c_list.len()
+                    && *k == &line[i..i + k.len()]
+                {
+                    first_digit = Some(*v);
+                    found = true;
                     break;
                 }
-                i += 1;
             }
-            if let None = first_digit {
-                println!("Invalid line: {}", line);
-                continue;
+            if found {
+                break;
             }
-            i = c_list.len()-1;
-            loop {
-                if c_list[i].is_digit(10) {
-                    last_digit = c_list[i].to_digit(10);
-                    break;
-                }
-                let mut found = false;
-                for (k, v) in &num_map {
-                    if i+k.len() <= c_list.len() && String::from(*k) == String::from(&line[i..i+k.len()]) {
-                        last_digit = Some(*v);
-                        found = true;
-                        break;
-                    }
-                }
-                if found || i == 0 {
+            i += 1;
+        }
+        if first_digit.is_none() {
+            event!(Level::Warn, "Invalid line: {}", line);
+            return None;
+        }
+        i = c_list.len() - 1;
+        loop {
+            if c_list[i].is_ascii_digit() {
+                last_digit = c_list[i].to_digit(10);
+                break;
+            }
+            let mut found = false;
+            for (k, v) in &SPELLED_DIGITS {
+                if i + k.len() <= c_list.len()
+                    && *k == &line[i..i + k.len()]
+                {
+                    last_digit = Some(*v);
+                    found = true;
                     break;
                 }
-                i -= 1;
             }
-            let curr_val = first_digit.unwrap() * 10 + last_digit.unwrap();
-            total += curr_val;
+            if found || i == 0 {
+                break;
+            }
+            i -= 1;
         }
-        total
+        Some(first_digit.unwrap() * 10 + last_digit.unwrap())
     }
+}
+
+impl Solution<u32, u32> for Day1 {
+    const MATERIALIZE: bool = false;
 
+    fn solve_part_one(input: &Input) -> u32 {
+        input.lines().filter_map(Self::calibration_value).sum()
+    }
+    fn solve_part_two(input: &Input) -> u32 {
+        input
+            .lines()
+            .filter_map(Self::spelled_calibration_value)
+            .sum()
+    }
+    fn stream_part_one(lines: LineStream) -> u32 {
+        lines
+            .filter_map(|line| Self::calibration_value(&line))
+            .sum()
+    }
+    fn stream_part_two(lines: LineStream) -> u32 {
+        lines
+            .filter_map(|line| Self::spelled_calibration_value(&line))
+            .sum()
+    }
 }
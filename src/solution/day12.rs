@@ -1,11 +1,15 @@
-use macros::return_type;
+use macros::{memoize, return_type};
 use std::iter;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
 
 #[return_type(p1 = u64, p2 = u64)]
 pub struct Day12;
 
+/// Day 12 counting part one's arrangements with the memoized recursion of
+/// part two instead of trying every assignment of the unknown springs.
+pub struct Day12Memoized;
+
 impl Day12 {
     fn parse_line(line: &str, num_copies: usize) -> (String, Vec<u32>) {
         let splitted_line = line.split(' ').collect::<Vec<_>>();
@@ -70,60 +74,61 @@ impl Day12 {
             })
             .count() as u64
     }
-    fn count_valid_arrangement_optimized(parsed_line: (String, Vec<u32>)) -> u64 {
-        let config_str = parsed_line.0.chars().collect::<Vec<_>>();
-        let mut dp: Vec<Vec<u64>> = vec![vec![0; parsed_line.1.len() + 1]; config_str.len() + 1];
-        dp[0][0] = 1;
-        for i in 1..=config_str.len() {
-            if config_str[i - 1] != '#' {
-                dp[i][0] = dp[i - 1][0];
-            }
-        }
-        let replaceable = |i: usize, j: usize| -> bool {
-            if i < parsed_line.1[j - 1] as usize {
-                return false;
-            }
-            for z in (i - parsed_line.1[j - 1] as usize)..i {
-                if config_str[z] == '.' {
-                    return false;
-                }
-            }
-            if (i as u32) - parsed_line.1[j - 1] >= 1
-                && config_str[i - (parsed_line.1[j - 1] as usize) - 1] == '#'
-            {
-                return false;
-            }
-            true
+    /// Number of ways to fit `groups` of damaged springs into `springs`.
+    #[memoize(key = (springs.len(), groups.len()))]
+    fn count_arrangements(springs: &[char], groups: &[u32]) -> u64 {
+        let Some(group) = groups.first().map(|x| *x as usize) else {
+            return if springs.contains(&'#') { 0 } else { 1 };
         };
-        for i in 1..=config_str.len() {
-            for j in 1..=parsed_line.1.len() {
-                if replaceable(i, j) {
-                    if i - (parsed_line.1[j - 1] as usize) >= 1 {
-                        dp[i][j] += dp[i - (parsed_line.1[j - 1] as usize) - 1][j - 1];
-                    } else {
-                        dp[i][j] += dp[i - (parsed_line.1[j - 1] as usize)][j - 1];
-                    }
-                    if config_str[i - 1] == '?' {
-                        dp[i][j] += dp[i - 1][j];
-                    }
-                } else if config_str[i - 1] != '#' {
-                    dp[i][j] += dp[i - 1][j];
-                }
-            }
+        let mut ret = 0;
+        if springs.first().is_some_and(|c| *c != '#') {
+            ret += Self::count_arrangements(&springs[1..], groups);
+        }
+        if springs.len() >= group
+            && springs[..group].iter().all(|c| *c != '.')
+            && springs.get(group) != Some(&'#')
+        {
+            let rest = springs.get(group + 1..).unwrap_or(&[]);
+            ret += Self::count_arrangements(rest, &groups[1..]);
         }
-        *dp.last().unwrap().last().unwrap()
+        ret
+    }
+    fn count_valid_arrangement_optimized(parsed_line: (String, Vec<u32>)) -> u64 {
+        let config_str = parsed_line.0.chars().collect::<Vec<_>>();
+        // The cache is keyed by suffix lengths, which only identify a
+        // subproblem within a single line.
+        Self::count_arrangements_cache_clear();
+        Self::count_arrangements(&config_str, &parsed_line.1)
     }
 }
 
 impl Solution<u64, u64> for Day12 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u64 {
-        lines
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u64 {
+        input
+            .lines()
             .map(|line| Self::count_valid_arrangement_bruteforce(Self::parse_line(line, 1)))
             .sum()
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u64 {
-        lines
+    fn solve_part_two(input: &Input) -> u64 {
+        input
+            .lines()
             .map(|line| Self::count_valid_arrangement_optimized(Self::parse_line(line, 5)))
             .sum()
     }
 }
+
+impl Solution<u64, u64> for Day12Memoized {
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u64 {
+        input
+            .lines()
+            .map(|line| Day12::count_valid_arrangement_optimized(Day12::parse_line(line, 1)))
+            .sum()
+    }
+    fn solve_part_two(input: &Input) -> u64 {
+        Day12::solve_part_two(input)
+    }
+}
@@ -1,25 +1,24 @@
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::grid::Grid;
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day13;
 
 struct PatternMap {
-    grid: Vec<Vec<char>>,
+    grid: Grid<char>,
 }
 
 impl PatternMap {
-    fn new(buffer: &Vec<Vec<char>>) -> PatternMap {
-        PatternMap {
-            grid: buffer.clone(),
-        }
+    fn new(grid: Grid<char>) -> PatternMap {
+        PatternMap { grid }
     }
     fn match_column(&self, i: usize, j: usize) -> bool {
-        (0..self.grid.len()).all(|k| self.grid[k][i] == self.grid[k][j])
+        self.grid.column(i).eq(self.grid.column(j))
     }
     fn match_row(&self, i: usize, j: usize) -> bool {
-        (0..self.grid[0].len()).all(|k| self.grid[i][k] == self.grid[j][k])
+        self.grid.row(i) == self.grid.row(j)
     }
     fn summarize(
         &self,
@@ -28,7 +27,7 @@ impl PatternMap {
     ) -> (u32, u32) {
         let mut vertical_value = 0u32;
         let mut horizontal_value = 0u32;
-        for i in 0..self.grid[0].len() - 1 {
+        for i in 0..self.grid.width() - 1 {
             let mut l = i;
             let mut r = i + 1;
             loop {
@@ -37,21 +36,21 @@ impl PatternMap {
                     r -= 1;
                     break;
                 }
-                if l == 0 || r == self.grid[0].len() - 1 {
+                if l == 0 || r == self.grid.width() - 1 {
                     break;
                 }
                 l -= 1;
                 r += 1;
             }
             if l <= r
-                && (l == 0 || r == self.grid[0].len() - 1)
+                && (l == 0 || r == self.grid.width() - 1)
                 && (i + 1) as u32 != invalid_vertical_reflection
             {
                 vertical_value = (i + 1) as u32;
                 break;
             }
         }
-        for i in 0..self.grid.len() - 1 {
+        for i in 0..self.grid.height() - 1 {
             let mut l = i;
             let mut r = i + 1;
             loop {
@@ -60,14 +59,14 @@ impl PatternMap {
                     r -= 1;
                     break;
                 }
-                if l == 0 || r == self.grid.len() - 1 {
+                if l == 0 || r == self.grid.height() - 1 {
                     break;
                 }
                 l -= 1;
                 r += 1;
             }
             if l <= r
-                && (l == 0 || r == self.grid.len() - 1)
+                && (l == 0 || r == self.grid.height() - 1)
                 && (i + 1) as u32 != invalid_horizontal_reflection
             {
                 horizontal_value = (i + 1) as u32;
@@ -78,56 +77,44 @@ impl PatternMap {
 }
 
 impl Day13 {
-    fn read_pattern_list<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<PatternMap> {
-        let mut current_buffer: Vec<Vec<char>> = Vec::new();
-        let mut patterns: Vec<PatternMap> = Vec::new();
-        for line in lines {
-            if line == "" {
-                patterns.push(PatternMap::new(&current_buffer));
-                current_buffer.clear();
-            } else {
-                current_buffer.push(line.chars().collect::<Vec<_>>());
-            }
-        }
-        if !current_buffer.is_empty() {
-            patterns.push(PatternMap::new(&current_buffer));
-        }
-        patterns
+    fn read_pattern_list(input: &Input) -> Vec<PatternMap> {
+        input
+            .sections()
+            .iter()
+            .map(|section| PatternMap::new(section.grid::<char>()))
+            .collect()
     }
 }
 
 impl Solution<u32, u32> for Day13 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let patterns = Self::read_pattern_list(lines);
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let patterns = Self::read_pattern_list(input);
         patterns
             .into_iter()
             .map(|pattern| {
                 let (vertical, horizontal) =
-                    pattern.summarize(pattern.grid.len() as u32, pattern.grid[0].len() as u32);
+                    pattern.summarize(pattern.grid.height() as u32, pattern.grid.width() as u32);
                 vertical + horizontal * 100
             })
             .sum()
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut patterns = Self::read_pattern_list(lines);
+    fn solve_part_two(input: &Input) -> u32 {
+        let mut patterns = Self::read_pattern_list(input);
         patterns
             .iter_mut()
             .map(|pattern| {
                 let (original_vertical_reflection, original_horizontal_reflection) =
-                    pattern.summarize(pattern.grid.len() as u32, pattern.grid[0].len() as u32);
-                for i in 0..pattern.grid.len() {
-                    for j in 0..pattern.grid[0].len() {
-                        pattern.grid[i][j] = if pattern.grid[i][j] == '#' { '.' } else { '#' };
-                        let (new_vertical_reflection, new_horizontal_reflection) = pattern
-                            .summarize(
-                                original_horizontal_reflection,
-                                original_vertical_reflection,
-                            );
-                        if new_horizontal_reflection != 0 || new_vertical_reflection != 0 {
-                            return new_horizontal_reflection * 100 + new_vertical_reflection;
-                        }
-                        pattern.grid[i][j] = if pattern.grid[i][j] == '#' { '.' } else { '#' };
+                    pattern.summarize(pattern.grid.height() as u32, pattern.grid.width() as u32);
+                for pos in pattern.grid.positions().collect::<Vec<_>>() {
+                    pattern.grid[pos] = if pattern.grid[pos] == '#' { '.' } else { '#' };
+                    let (new_vertical_reflection, new_horizontal_reflection) = pattern
+                        .summarize(original_horizontal_reflection, original_vertical_reflection);
+                    if new_horizontal_reflection != 0 || new_vertical_reflection != 0 {
+                        return new_horizontal_reflection * 100 + new_vertical_reflection;
                     }
+                    pattern.grid[pos] = if pattern.grid[pos] == '#' { '.' } else { '#' };
                 }
                 panic!("Cannot find valid smudge :(");
             })
@@ -1,18 +1,22 @@
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::animate::{self, Frame};
+use crate::utils::cycle;
+use crate::utils::grid::{Grid, Pos};
+use crate::utils::render::{self, Color, Picture};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day14;
 
 impl Day14 {
-    fn roll_north(grid: &mut Vec<Vec<char>>) {
-        for j in 0..grid[0].len() {
-            for i in 1..grid.len() {
-                if grid[i][j] == 'O' {
+    fn roll_north(grid: &mut Grid<char>) {
+        for j in 0..grid.width() {
+            for i in 1..grid.height() {
+                if grid[Pos::new(i, j)] == 'O' {
                     let mut fillable_index = i - 1;
                     loop {
-                        if grid[fillable_index][j] != '.' {
+                        if grid[Pos::new(fillable_index, j)] != '.' {
                             fillable_index += 1;
                             break;
                         }
@@ -21,19 +25,19 @@ impl Day14 {
                         }
                         fillable_index -= 1;
                     }
-                    grid[i][j] = '.';
-                    grid[fillable_index][j] = 'O';
+                    grid[Pos::new(i, j)] = '.';
+                    grid[Pos::new(fillable_index, j)] = 'O';
                 }
             }
         }
     }
-    fn roll_west(grid: &mut Vec<Vec<char>>) {
-        for i in 0..grid.len() {
-            for j in 1..grid[0].len() {
-                if grid[i][j] == 'O' {
+    fn roll_west(grid: &mut Grid<char>) {
+        for i in 0..grid.height() {
+            for j in 1..grid.width() {
+                if grid[Pos::new(i, j)] == 'O' {
                     let mut fillable_index = j - 1;
                     loop {
-                        if grid[i][fillable_index] != '.' {
+                        if grid[Pos::new(i, fillable_index)] != '.' {
                             fillable_index += 1;
                             break;
                         }
@@ -42,97 +46,119 @@ impl Day14 {
                         }
                         fillable_index -= 1;
                     }
-                    grid[i][j] = '.';
-                    grid[i][fillable_index] = 'O';
+                    grid[Pos::new(i, j)] = '.';
+                    grid[Pos::new(i, fillable_index)] = 'O';
                 }
             }
         }
     }
 
-    fn roll_south(grid: &mut Vec<Vec<char>>) {
-        for j in 0..grid[0].len() {
-            for i in (0..grid.len() - 1).rev() {
-                if grid[i][j] == 'O' {
+    fn roll_south(grid: &mut Grid<char>) {
+        for j in 0..grid.width() {
+            for i in (0..grid.height() - 1).rev() {
+                if grid[Pos::new(i, j)] == 'O' {
                     let mut fillable_index = i + 1;
                     loop {
-                        if grid[fillable_index][j] != '.' {
+                        if grid[Pos::new(fillable_index, j)] != '.' {
                             fillable_index -= 1;
                             break;
                         }
-                        if fillable_index == grid.len() - 1 {
+                        if fillable_index == grid.height() - 1 {
                             break;
                         }
                         fillable_index += 1;
                     }
-                    grid[i][j] = '.';
-                    grid[fillable_index][j] = 'O';
+                    grid[Pos::new(i, j)] = '.';
+                    grid[Pos::new(fillable_index, j)] = 'O';
                 }
             }
         }
     }
 
-    fn roll_east(grid: &mut Vec<Vec<char>>) {
-        for i in 0..grid.len() {
-            for j in (0..grid[0].len() - 1).rev() {
-                if grid[i][j] == 'O' {
+    fn roll_east(grid: &mut Grid<char>) {
+        for i in 0..grid.height() {
+            for j in (0..grid.width() - 1).rev() {
+                if grid[Pos::new(i, j)] == 'O' {
                     let mut fillable_index = j + 1;
                     loop {
-                        if grid[i][fillable_index] != '.' {
+                        if grid[Pos::new(i, fillable_index)] != '.' {
                             fillable_index -= 1;
                             break;
                         }
-                        if fillable_index == grid[0].len() - 1 {
+                        if fillable_index == grid.width() - 1 {
                             break;
                         }
                         fillable_index += 1;
                     }
-                    grid[i][j] = '.';
-                    grid[i][fillable_index] = 'O';
+                    grid[Pos::new(i, j)] = '.';
+                    grid[Pos::new(i, fillable_index)] = 'O';
                 }
             }
         }
     }
 
-    fn calculate_weight(grid: &Vec<Vec<char>>) -> u32 {
-        (0..grid.len())
-            .map(|i| grid[i].iter().filter(|x| *x == &'O').count() * (grid.len() - i))
+    fn calculate_weight(grid: &Grid<char>) -> u32 {
+        grid.rows()
+            .enumerate()
+            .map(|(i, row)| row.iter().filter(|x| *x == &'O').count() * (grid.height() - i))
             .sum::<usize>() as u32
     }
-    fn rotate_cycle(mut grid: Vec<Vec<char>>) -> Vec<Vec<char>> {
-        Self::roll_north(&mut grid);
-        Self::roll_west(&mut grid);
-        Self::roll_south(&mut grid);
-        Self::roll_east(&mut grid);
-        grid
+    fn picture(grid: &Grid<char>) -> Picture {
+        Picture::new(grid, |c| match c {
+            'O' => Color::YELLOW,
+            '#' => Color::GRAY,
+            _ => Color::DARK,
+        })
+    }
+    fn frame(caption: impl std::fmt::Display, grid: &Grid<char>) -> Frame {
+        Frame::from_grid(caption, grid, |_, c| {
+            let color = match c {
+                'O' => Some(Color::YELLOW),
+                '#' => Some(Color::GRAY),
+                _ => None,
+            };
+            (*c, color)
+        })
     }
-    fn is_same_grid(g1: &Vec<Vec<char>>, g2: &Vec<Vec<char>>) -> bool {
-        (0..g1.len()).all(|i| (0..g1[i].len()).all(|j| g1[i][j] == g2[i][j]))
+    fn rotate_cycle(mut grid: Grid<char>, cycle: usize) -> Grid<char> {
+        let rolls = [
+            ("north", Self::roll_north as fn(&mut Grid<char>)),
+            ("west", Self::roll_west),
+            ("south", Self::roll_south),
+            ("east", Self::roll_east),
+        ];
+        for (direction, roll) in rolls {
+            roll(&mut grid);
+            animate::frame(|| {
+                Self::frame(format_args!("Cycle {}, tilted {}", cycle, direction), &grid)
+            });
+        }
+        grid
     }
 }
 
 impl Solution<u32, u32> for Day14 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut grid = lines
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let mut grid = input.grid::<char>();
+        animate::frame(|| Self::frame("Before tilting", &grid));
         Self::roll_north(&mut grid);
+        animate::frame(|| Self::frame("Tilted north", &grid));
+        render::emit("day14-tilted", || Self::picture(&grid));
         Self::calculate_weight(&grid)
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut grid_list = vec![lines
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>()];
-        let target_num_cycle = 1000000000;
-        loop {
-            let new_grid = Self::rotate_cycle(grid_list.last().unwrap().clone());
-            for i in 0..grid_list.len() {
-                if Self::is_same_grid(&new_grid, &grid_list[i]) {
-                    return Self::calculate_weight(
-                        &grid_list[(target_num_cycle - i) % (grid_list.len() - i) + i],
-                    );
-                }
-            }
-            grid_list.push(new_grid);
-        }
+    fn solve_part_two(input: &Input) -> u32 {
+        let mut cycles = 0;
+        let grid = cycle::state_after(
+            input.grid::<char>(),
+            |grid| {
+                cycles += 1;
+                Self::rotate_cycle(grid.clone(), cycles)
+            },
+            1000000000,
+        );
+        render::emit("day14-cycled", || Self::picture(&grid));
+        Self::calculate_weight(&grid)
     }
 }
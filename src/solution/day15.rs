@@ -1,13 +1,13 @@
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, LineStream, Solution};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day15;
 
-enum Operation<'a> {
-    Remove(usize, &'a str),
-    Add(usize, &'a str, u32),
+enum Operation {
+    Remove(usize, String),
+    Add(usize, String, u32),
 }
 
 impl Day15 {
@@ -25,39 +25,20 @@ impl Day15 {
         if parsed_raw_op.len() == 2 {
             Operation::Add(
                 Self::calculate_hash(parsed_raw_op[0]) as usize,
-                parsed_raw_op[0],
+                String::from(parsed_raw_op[0]),
                 parsed_raw_op[1].parse::<u32>().unwrap(),
             )
         } else {
             assert!(parsed_raw_op.len() == 1);
             Operation::Remove(
                 Self::calculate_hash(parsed_raw_op[0]) as usize,
-                parsed_raw_op[0],
+                String::from(parsed_raw_op[0]),
             )
         }
     }
-}
-
-impl Solution<u32, u32> for Day15 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        lines
-            .into_iter()
-            .next()
-            .unwrap()
-            .split(',')
-            .map(Self::calculate_hash)
-            .sum()
-    }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut boxes: Vec<Vec<(&str, u32)>> = vec![Vec::new(); 256];
-        let operations = lines
-            .into_iter()
-            .next()
-            .unwrap()
-            .split(',')
-            .map(Self::parse_operation)
-            .collect::<Vec<_>>();
-        for operation in operations.into_iter() {
+    fn focusing_power(operations: impl Iterator<Item = Operation>) -> u32 {
+        let mut boxes: Vec<Vec<(String, u32)>> = vec![Vec::new(); 256];
+        for operation in operations {
             match operation {
                 Operation::Add(box_index, key, value) => {
                     if let Some(target_index) = boxes[box_index]
@@ -100,3 +81,38 @@ impl Solution<u32, u32> for Day15 {
             .sum()
     }
 }
+
+impl Solution<u32, u32> for Day15 {
+    const MATERIALIZE: bool = false;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        input
+            .lines()
+            .next()
+            .unwrap()
+            .trim()
+            .split(',')
+            .map(Self::calculate_hash)
+            .sum()
+    }
+    fn solve_part_two(input: &Input) -> u32 {
+        Self::focusing_power(
+            input
+                .lines()
+                .next()
+                .unwrap()
+                .trim()
+                .split(',')
+                .map(Self::parse_operation),
+        )
+    }
+    fn stream_part_one(lines: LineStream) -> u32 {
+        lines
+            .records(b',')
+            .map(|step| Self::calculate_hash(&step))
+            .sum()
+    }
+    fn stream_part_two(lines: LineStream) -> u32 {
+        Self::focusing_power(lines.records(b',').map(|step| Self::parse_operation(&step)))
+    }
+}
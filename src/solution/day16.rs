@@ -2,65 +2,52 @@ use std::collections::VecDeque;
 
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::animate::{self, Frame};
+use crate::utils::counters;
+use crate::utils::direction::Direction;
+use crate::utils::grid::{Grid, Pos};
+use crate::utils::render::{self, Color, Picture};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day16;
 
-const DIR_VECTOR: &[(i32, i32)] = &[(-1, 0), (0, 1), (1, 0), (0, -1)];
-const DIR_UP: usize = 0;
-const DIR_RIGHT: usize = 1;
-const DIR_DOWN: usize = 2;
-const DIR_LEFT: usize = 3;
-
-struct Grid {
-    g: Vec<Vec<char>>,
+struct Contraption {
+    g: Grid<char>,
 }
 
-impl Grid {
-    fn new(grid: Vec<Vec<char>>) -> Grid {
-        Grid { g: grid }
-    }
-    fn rotate_clockwise(dir_index: usize) -> usize {
-        (dir_index + 1) % 4
+impl Contraption {
+    fn new(grid: Grid<char>) -> Contraption {
+        Contraption { g: grid }
     }
-    fn rotate_counterclockwise(dir_index: usize) -> usize {
-        (dir_index + 3) % 4
-    }
-    fn generate_neighbors(cell_type: char, dir: usize) -> Vec<usize> {
+    fn generate_neighbors(cell_type: char, dir: Direction) -> Vec<Direction> {
         match cell_type {
             '|' => {
-                if dir == DIR_UP || dir == DIR_DOWN {
+                if dir == Direction::Up || dir == Direction::Down {
                     vec![dir]
                 } else {
-                    vec![
-                        Self::rotate_counterclockwise(dir),
-                        Self::rotate_clockwise(dir),
-                    ]
+                    vec![dir.turn_left(), dir.turn_right()]
                 }
             }
             '-' => {
-                if dir == DIR_LEFT || dir == DIR_RIGHT {
+                if dir == Direction::Left || dir == Direction::Right {
                     vec![dir]
                 } else {
-                    vec![
-                        Self::rotate_counterclockwise(dir),
-                        Self::rotate_clockwise(dir),
-                    ]
+                    vec![dir.turn_left(), dir.turn_right()]
                 }
             }
             '/' => {
-                if dir == DIR_RIGHT || dir == DIR_LEFT {
-                    vec![Self::rotate_counterclockwise(dir)]
+                if dir == Direction::Right || dir == Direction::Left {
+                    vec![dir.turn_left()]
                 } else {
-                    vec![Self::rotate_clockwise(dir)]
+                    vec![dir.turn_right()]
                 }
             }
             '\\' => {
-                if dir == DIR_DOWN || dir == DIR_UP {
-                    vec![Self::rotate_counterclockwise(dir)]
+                if dir == Direction::Down || dir == Direction::Up {
+                    vec![dir.turn_left()]
                 } else {
-                    vec![Self::rotate_clockwise(dir)]
+                    vec![dir.turn_right()]
                 }
             }
             '.' => {
@@ -71,74 +58,116 @@ impl Grid {
             }
         }
     }
-    fn count_num_energized(
-        // grid: &Vec<Vec<char>>,
-        &self,
-        start_pos: (usize, usize),
-        start_dir: usize,
-    ) -> u32 {
-        let mut visited: Vec<Vec<Vec<bool>>> =
-            vec![vec![vec![false; 4]; self.g[0].len()]; self.g.len()];
-        let mut q: VecDeque<(usize, usize, usize)> = VecDeque::new();
-        q.push_back((start_pos.0, start_pos.1, start_dir));
-        visited[start_pos.0][start_pos.1][start_dir] = true;
-        while let Some((x, y, dir)) = q.pop_front() {
-            for neighbor in Self::generate_neighbors(self.g[x][y], dir)
+    /// For every tile, the directions beams pass through it in. If
+    /// `animated`, a frame shows how far the beams got after every step.
+    fn beams(&self, start_pos: Pos, start_dir: Direction, animated: bool) -> Grid<[bool; 4]> {
+        let mut visited: Grid<[bool; 4]> = Grid::new(self.g.width(), self.g.height(), [false; 4]);
+        let mut q: VecDeque<(Pos, Direction)> = VecDeque::new();
+        q.push_back((start_pos, start_dir));
+        visited[start_pos][start_dir.cardinal_index()] = true;
+        // The queue holds the beams of one step before those of the next.
+        let (mut step, mut left_in_step) = (0, q.len());
+        while let Some((pos, dir)) = q.pop_front() {
+            counters::increment("beam states");
+            for (npos, ndir) in Self::generate_neighbors(self.g[pos], dir)
                 .into_iter()
                 .flat_map(|new_dir| {
-                    let nx = x.checked_add_signed(DIR_VECTOR[new_dir].0 as isize);
-                    let ny = y.checked_add_signed(DIR_VECTOR[new_dir].1 as isize);
-                    if nx.is_none() || ny.is_none() {
-                        None
-                    } else {
-                        Some((nx.unwrap(), ny.unwrap(), new_dir))
-                    }
+                    self.g
+                        .step(pos, new_dir.delta())
+                        .map(|npos| (npos, new_dir))
                 })
             {
-                if neighbor.0 < self.g.len()
-                    && neighbor.1 < self.g[0].len()
-                    && !visited[neighbor.0][neighbor.1][neighbor.2]
-                {
-                    q.push_back(neighbor);
-                    visited[neighbor.0][neighbor.1][neighbor.2] = true;
+                if !visited[npos][ndir.cardinal_index()] {
+                    q.push_back((npos, ndir));
+                    visited[npos][ndir.cardinal_index()] = true;
+                }
+            }
+            left_in_step -= 1;
+            if left_in_step == 0 {
+                step += 1;
+                left_in_step = q.len();
+                if animated {
+                    animate::frame(|| self.frame(step, &visited, &q));
                 }
             }
         }
-        let mut ret = 0;
-        for i in 0..self.g.len() {
-            for j in 0..self.g[i].len() {
-                if visited[i][j].iter().any(|x| *x) {
-                    ret += 1;
+        visited
+    }
+    /// The contraption with every beam so far drawn as in the puzzle: an
+    /// arrow where one passed, the number of them where more did. The ends
+    /// of the beams are highlighted.
+    fn frame(
+        &self,
+        step: usize,
+        visited: &Grid<[bool; 4]>,
+        ends: &VecDeque<(Pos, Direction)>,
+    ) -> Frame {
+        let mut frame = Frame::from_grid(format_args!("Step {}", step), &self.g, |pos, c| {
+            let dirs = visited[pos].iter().filter(|x| **x).count();
+            let color = match dirs {
+                0 => None,
+                _ => Some(Color::YELLOW.mix(Color::DARK, 0.4)),
+            };
+            match (c, dirs) {
+                ('.', 0) => ('.', None),
+                ('.', 1) => {
+                    let dir = visited[pos].iter().position(|x| *x).unwrap();
+                    (['^', '>', 'v', '<'][dir], color)
                 }
+                ('.', n) => (char::from_digit(n as u32, 10).unwrap(), color),
+                (c, _) => (*c, color.or(Some(Color::GRAY))),
             }
+        });
+        for (pos, _) in ends {
+            frame.highlight(*pos, Color::YELLOW);
         }
-        ret
+        frame
     }
-}
-
-impl Day16 {
-    fn parse_grid<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<Vec<char>> {
-        lines
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>()
+    fn num_energized(beams: &Grid<[bool; 4]>) -> u32 {
+        beams
+            .iter()
+            .filter(|(_, dirs)| dirs.iter().any(|x| *x))
+            .count() as u32
+    }
+    fn count_num_energized(&self, start_pos: Pos, start_dir: Direction) -> u32 {
+        Self::num_energized(&self.beams(start_pos, start_dir, false))
+    }
+    fn picture(&self, beams: &Grid<[bool; 4]>) -> Picture {
+        let mut picture = Picture::new(&self.g, |c| match c {
+            '.' => Color::DARK,
+            _ => Color::GRAY,
+        });
+        for (pos, _) in beams.iter().filter(|(_, dirs)| dirs.iter().any(|x| *x)) {
+            let color = match self.g[pos] {
+                '.' => Color::YELLOW,
+                _ => Color::WHITE,
+            };
+            picture.fill(pos, color);
+        }
+        picture
     }
 }
 
 impl Solution<u32, u32> for Day16 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let grid = Grid::new(Self::parse_grid(lines));
-        grid.count_num_energized((0, 0), DIR_RIGHT)
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let grid = Contraption::new(input.grid::<char>());
+        let beams = grid.beams(Pos::new(0, 0), Direction::Right, true);
+        render::emit("day16-energized", || grid.picture(&beams));
+        Contraption::num_energized(&beams)
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let grid = Grid::new(Self::parse_grid(lines));
-        (0..grid.g.len())
+    fn solve_part_two(input: &Input) -> u32 {
+        let grid = Contraption::new(input.grid::<char>());
+        let (width, height) = (grid.g.width(), grid.g.height());
+        (0..height)
             .fold(0, |acc, i| {
-                acc.max(grid.count_num_energized((i, 0), DIR_RIGHT))
-                    .max(grid.count_num_energized((i, grid.g[0].len() - 1), DIR_LEFT))
+                acc.max(grid.count_num_energized(Pos::new(i, 0), Direction::Right))
+                    .max(grid.count_num_energized(Pos::new(i, width - 1), Direction::Left))
             })
-            .max((0..grid.g[0].len()).fold(0, |acc, i| {
-                acc.max(grid.count_num_energized((0, i), DIR_DOWN))
-                    .max(grid.count_num_energized((grid.g.len() - 1, i), DIR_UP))
+            .max((0..width).fold(0, |acc, i| {
+                acc.max(grid.count_num_energized(Pos::new(0, i), Direction::Down))
+                    .max(grid.count_num_energized(Pos::new(height - 1, i), Direction::Up))
             }))
     }
 }
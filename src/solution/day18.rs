@@ -1,85 +1,97 @@
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::direction::Direction;
+use crate::utils::geometry::{Location, Polygon};
+use crate::utils::grid::Grid;
+use crate::utils::render::{self, Color, Picture};
 use macros::return_type;
-use std::collections::HashMap;
 
 #[return_type(p1 = u32, p2 = u64)]
 pub struct Day18;
 
 impl Day18 {
-    fn parse_line(line: &str) -> (char, u32, String) {
+    fn parse_line(line: &str) -> (Direction, u32, String) {
         let splitted = line.split(' ').collect::<Vec<_>>();
-        (splitted[0].chars().next().unwrap(), splitted[1].parse::<u32>().unwrap(), splitted[2].replace(['(', ')'], ""))
-    }   
+        let dir_char = splitted[0].chars().next().unwrap();
+        (
+            Direction::from_char(dir_char)
+                .unwrap_or_else(|| panic!("Invalid direction found: {}", dir_char)),
+            splitted[1].parse::<u32>().unwrap(),
+            splitted[2].replace(['(', ')'], ""),
+        )
+    }
+    fn decode_color(color: &str) -> (Direction, i64) {
+        let dir_list = [
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+        ];
+        let hex_data = color.trim_start_matches('#');
+        let (distance, dir) = hex_data.split_at(hex_data.len() - 1);
+        (
+            dir_list[dir.parse::<usize>().unwrap()],
+            i64::from_str_radix(distance, 16).unwrap(),
+        )
+    }
+    fn trench(instructions: impl Iterator<Item = (Direction, i64)>) -> Polygon {
+        let mut current_point = (0i64, 0i64);
+        Polygon::new(
+            instructions
+                .map(|(dir, distance)| {
+                    current_point = dir.advance(current_point, distance);
+                    current_point
+                })
+                .collect(),
+        )
+    }
+    /// Number of cubic meters dug out: the trench itself plus everything it
+    /// encloses.
+    fn lagoon_size(trench: &Polygon) -> u64 {
+        // Quadratic in the number of edges, so only checked in debug builds.
+        debug_assert!(
+            !trench.is_self_intersecting(),
+            "Dig plan trench crosses itself"
+        );
+        (trench.boundary_points() + trench.interior_points()) as u64
+    }
+    /// The trench and the lagoon inside it, from above.
+    fn picture(trench: &Polygon) -> Picture {
+        let rows = trench.vertices.iter().map(|p| p.0);
+        let cols = trench.vertices.iter().map(|p| p.1);
+        let (top, bottom) = (rows.clone().min().unwrap(), rows.max().unwrap());
+        let (left, right) = (cols.clone().min().unwrap(), cols.max().unwrap());
+        let (height, width) = (bottom - top + 1, right - left + 1);
+        let grid = Grid::new(width as usize, height as usize, ());
+        let mut picture = Picture::new(&grid, |_| Color::DARK);
+        for pos in grid.positions() {
+            let point = (pos.row as i64 + top, pos.col as i64 + left);
+            match trench.locate(point) {
+                Location::Boundary => picture.fill(pos, Color::BROWN),
+                Location::Inside => picture.fill(pos, Color::BLUE),
+                Location::Outside => {}
+            }
+        }
+        picture
+    }
 }
 
 impl Solution<u32, u64> for Day18 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut points: Vec<(i32, i32)> = Vec::new();
-        let mut point_color: HashMap<(i32, i32), String> = HashMap::new();
-        let parsed_entries = lines.map(Self::parse_line);
-        let mut current_point = (0i32, 0i32);        
-        
-        for entry in parsed_entries {
-            for _iter in 0..entry.1 {
-                current_point = match entry.0 {
-                    'U' => (current_point.0, current_point.1 + 1),
-                    'R' => (current_point.0 + 1, current_point.1),
-                    'L' => (current_point.0 - 1, current_point.1),
-                    'D' => (current_point.0, current_point.1 - 1),
-                    _ => {
-                        panic!("Invalid direction found: {}", entry.0)
-                    }
-                };
-                points.push(current_point);
-                assert!(!point_color.contains_key(&current_point));
-                point_color.insert(current_point.clone(), entry.2.clone());
-            }
-        }
-        let area = (points.iter().enumerate().fold(0i32, |acc, curr| {
-            let p = if curr.0 == 0 {
-                points.last().unwrap()
-            } else {
-                &points[curr.0-1]
-            };
-            acc + (p.0 - curr.1.0) * (p.1 + curr.1.1)
-        }).abs() / 2) as u32;
-        let internal = area + 1 - (((points.len() / 2)) as u32);
-        points.len()  as u32 + internal
-        
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let trench = Self::trench(input.lines().map(|line| {
+            let (dir, distance, _) = Self::parse_line(line);
+            (dir, distance as i64)
+        }));
+        // Only the first dig plan is small enough to draw.
+        render::emit("day18-lagoon", || Self::picture(&trench));
+        Self::lagoon_size(&trench) as u32
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u64 {
-        let mut points: Vec<(i64, i64)> = Vec::new();
-        let dir_list = vec!['R', 'D', 'L', 'U'];
-        let mut current_point = (0i64, 0i64);        
-        let mut num_total_points = 0i64;
-        for line in lines {
-            let parsed_inst = Self::parse_line(line);
-            let mut hex_data = parsed_inst.2.chars().collect::<Vec<_>>();
-            hex_data.drain(0..1);
-            let dir = dir_list[hex_data.pop().unwrap().to_digit(10).unwrap() as usize];
-            let delta = i64::from_str_radix(hex_data.into_iter().fold(String::new(), |mut acc, curr| { 
-                acc.push(curr);
-                acc
-            }).as_str(), 16).unwrap();
-            current_point = match dir {
-                'U' => (current_point.0, current_point.1 + delta),
-                'R' => (current_point.0 + delta, current_point.1),
-                'L' => (current_point.0 - delta, current_point.1),
-                'D' => (current_point.0, current_point.1 - delta),
-                _ => {
-                    panic!("Invalid direction found: {}", dir)
-                }
-            };
-            points.push(current_point);
-            num_total_points += delta;
-        }
-        (points.iter().enumerate().fold(0i64, |acc, curr| {
-            let p = if curr.0 == 0 {
-                points.last().unwrap()
-            } else {
-                &points[curr.0-1]
-            };
-            acc + (p.0 - curr.1.0) * (p.1 + curr.1.1)
-        }).abs() / 2) as u64 + 1 + ((num_total_points / 2) as u64)
+    fn solve_part_two(input: &Input) -> u64 {
+        Self::lagoon_size(&Self::trench(
+            input
+                .lines()
+                .map(|line| Self::decode_color(&Self::parse_line(line).2)),
+        ))
     }
 }
@@ -1,6 +1,9 @@
 use std::collections::{HashMap, VecDeque};
+use std::fmt;
 
-use crate::solution::Solution;
+use crate::solution::{Input as PuzzleInput, Solution};
+use crate::utils::interval::{Interval, IntervalBox};
+use crate::utils::trace::{event, Level};
 use macros::return_type;
 
 #[return_type(p1 = u32, p2 = u64)]
@@ -24,12 +27,8 @@ struct Input {
     s: u32,
 }
 
-struct InputBound {
-    x: (u32, u32),
-    m: (u32, u32),
-    a: (u32, u32),
-    s: (u32, u32),
-}
+/// Ranges of `x`, `m`, `a` and `s` ratings, in that axis order.
+type InputBound = IntervalBox<u32, 4>;
 
 impl WorkflowManager {
     fn new(wm: HashMap<String, Vec<Rule>>) -> WorkflowManager {
@@ -37,28 +36,30 @@ impl WorkflowManager {
             workflow_mapping: wm,
         }
     }
-    fn accept_input(&self, input: &Input, workflow: String) -> bool {
-        if workflow == "A" {
-            return true;
-        }
-        if workflow == "R" {
-            return false;
-        }
-        if !self.workflow_mapping.contains_key(&workflow) {
-            panic!("Cannot find rules for workflow: {}", workflow);
-        }
-        let current_rules = self.workflow_mapping.get(&workflow).unwrap();
-        for rule in current_rules {
-            if rule.accept(input) {
-                return self.accept_input(input, rule.destination.clone());
-            }
+    /// Workflows `input` goes through from `workflow`, ending with `A` or
+    /// `R`.
+    fn workflow_chain<'a>(&'a self, input: &Input, mut workflow: &'a str) -> Vec<&'a str> {
+        let mut ret = vec![workflow];
+        while workflow != "A" && workflow != "R" {
+            let current_rules = self
+                .workflow_mapping
+                .get(workflow)
+                .unwrap_or_else(|| panic!("Cannot find rules for workflow: {}", workflow));
+            workflow = &current_rules
+                .iter()
+                .find(|rule| rule.accept(input))
+                .unwrap_or_else(|| panic!("No usable rules found for workflow {}", workflow))
+                .destination;
+            ret.push(workflow);
         }
-        panic!("No usable rules found for workflow {}", workflow)
+        ret
     }
     fn process_input(&self, input_data: &Vec<Input>) -> Vec<u32> {
         let mut ret = Vec::new();
         for input in input_data {
-            if self.accept_input(input, String::from("in")) {
+            let chain = self.workflow_chain(input, "in");
+            event!(Level::Debug, "{} goes {}", input, chain.join(" -> "));
+            if chain.last() == Some(&"A") {
                 ret.push(input.combined_params());
             }
         }
@@ -69,7 +70,7 @@ impl WorkflowManager {
         let mut acceptable_ranges: Vec<InputBound> = Vec::new();
         q.push_back((String::from("in"), init_bound));
         while let Some((workflow, mut current_range)) = q.pop_front() {
-            if !current_range.is_valid() || workflow == "R" {
+            if workflow == "R" {
                 continue;
             }
             if workflow == "A" {
@@ -80,11 +81,14 @@ impl WorkflowManager {
                 panic!("Cannot find rules for workflow: {}", workflow);
             }
             let current_rules = self.workflow_mapping.get(&workflow).unwrap();
-            for (i, rule) in current_rules.iter().enumerate() {
-                let new_range = rule.determine_new_range(&current_range);
-                q.push_back((rule.destination.clone(), new_range));
-                if i != current_rules.len() - 1 {
-                    current_range = rule.determine_new_range_inverted(&current_range);
+            for rule in current_rules {
+                let (new_range, remaining_range) = rule.split_range(&current_range);
+                if let Some(new_range) = new_range {
+                    q.push_back((rule.destination.clone(), new_range));
+                }
+                match remaining_range {
+                    Some(remaining_range) => current_range = remaining_range,
+                    None => break,
                 }
             }
         }
@@ -130,62 +134,32 @@ impl Rule {
             }
         }
     }
-    fn determine_new_range_inverted(&self, old_range: &InputBound) -> InputBound {
-        let mut new_range = InputBound {
-            x: old_range.x,
-            m: old_range.m,
-            a: old_range.a,
-            s: old_range.s,
-        };
-        if self.operator == '<' {
-            match self.variable.as_str() {
-                "x" => new_range.x.0 = new_range.x.0.max(self.value),
-                "m" => new_range.m.0 = new_range.m.0.max(self.value),
-                "a" => new_range.a.0 = new_range.a.0.max(self.value),
-                "s" => new_range.s.0 = new_range.s.0.max(self.value),
-                _ => panic!("invalid variable found: {}", self.variable),
-            }
-        } else if self.operator == '>' {
-            match self.variable.as_str() {
-                "x" => new_range.x.1 = new_range.x.1.min(self.value),
-                "m" => new_range.m.1 = new_range.m.1.min(self.value),
-                "a" => new_range.a.1 = new_range.a.1.min(self.value),
-                "s" => new_range.s.1 = new_range.s.1.min(self.value),
-                _ => panic!("invalid variable found: {}", self.variable),
-            };
-        } else {
-            panic!("Invalid operator found: {}", self.operator);
+    fn axis(&self) -> usize {
+        match self.variable.as_str() {
+            "x" => 0,
+            "m" => 1,
+            "a" => 2,
+            "s" => 3,
+            _ => panic!("invalid variable found: {}", self.variable),
         }
-        new_range
     }
-
-    fn determine_new_range(&self, old_range: &InputBound) -> InputBound {
-        let mut new_range = InputBound {
-            x: old_range.x,
-            m: old_range.m,
-            a: old_range.a,
-            s: old_range.s,
-        };
-        if self.operator == '<' {
-            match self.variable.as_str() {
-                "x" => new_range.x.1 = new_range.x.1.min(self.value - 1),
-                "m" => new_range.m.1 = new_range.m.1.min(self.value - 1),
-                "a" => new_range.a.1 = new_range.a.1.min(self.value - 1),
-                "s" => new_range.s.1 = new_range.s.1.min(self.value - 1),
-                _ => panic!("invalid variable found: {}", self.variable),
-            };
-        } else if self.operator == '>' {
-            match self.variable.as_str() {
-                "x" => new_range.x.0 = new_range.x.0.max(self.value + 1),
-                "m" => new_range.m.0 = new_range.m.0.max(self.value + 1),
-                "a" => new_range.a.0 = new_range.a.0.max(self.value + 1),
-                "s" => new_range.s.0 = new_range.s.0.max(self.value + 1),
-                _ => panic!("invalid variable found: {}", self.variable),
+    /// Splits a range into the part this rule accepts and the part passed on
+    /// to the next rule of the workflow.
+    fn split_range(&self, old_range: &InputBound) -> (Option<InputBound>, Option<InputBound>) {
+        match self.operator {
+            '<' => old_range.split_at(self.axis(), self.value),
+            '>' => {
+                let (below, above) = old_range.split_at(self.axis(), self.value + 1);
+                (above, below)
             }
-        } else {
-            panic!("Invalid operator found: {}", self.operator);
+            _ => panic!("Invalid operator found: {}", self.operator),
         }
-        new_range
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{x={},m={},a={},s={}}}", self.x, self.m, self.a, self.s)
     }
 }
 
@@ -220,18 +194,6 @@ impl Input {
     }
 }
 
-impl InputBound {
-    fn is_valid(&self) -> bool {
-        self.x.0 <= self.x.1 && self.m.0 <= self.m.1 && self.a.0 <= self.a.1 && self.s.0 <= self.s.1
-    }
-    fn count_valid_combinations(&self) -> u64 {
-        ((self.x.1 - self.x.0 + 1) as u64)
-            * ((self.m.1 - self.m.0 + 1) as u64)
-            * ((self.a.1 - self.a.0 + 1) as u64)
-            * ((self.s.1 - self.s.0 + 1) as u64)
-    }
-}
-
 impl Day19 {
     fn parse_workflow_data(raw_data: &str) -> (String, Vec<Rule>) {
         let splitted_data = raw_data.split(['{', '}']).collect::<Vec<_>>();
@@ -243,56 +205,32 @@ impl Day19 {
                 .collect::<Vec<_>>(),
         )
     }
+    fn parse_input(input: &PuzzleInput) -> (HashMap<String, Vec<Rule>>, Vec<Input>) {
+        let sections = input.sections();
+        let workflow_rules = sections[0].lines().map(Self::parse_workflow_data).collect();
+        let input_data = sections
+            .get(1)
+            .map(|section| section.lines().map(Input::new).collect())
+            .unwrap_or_default();
+        (workflow_rules, input_data)
+    }
 }
 
 impl Solution<u32, u64> for Day19 {
-    fn part_one<'a>(lines_it: impl Iterator<Item = &'a str>) -> u32 {
-        let lines = lines_it.collect::<Vec<_>>();
-        let mut workflow_rules: HashMap<String, Vec<Rule>> = HashMap::new();
-        let mut workflow_ended = false;
-        let mut input_data: Vec<Input> = Vec::new();
-        for line in lines {
-            if line.is_empty() {
-                workflow_ended = true;
-                continue;
-            }
-            if !workflow_ended {
-                let (k, v) = Self::parse_workflow_data(line);
-                workflow_rules.insert(k, v);
-            } else {
-                input_data.push(Input::new(line));
-            }
-        }
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &PuzzleInput) -> u32 {
+        let (workflow_rules, input_data) = Self::parse_input(input);
         let workflow_manager = WorkflowManager::new(workflow_rules);
         workflow_manager.process_input(&input_data).iter().sum()
     }
-    fn part_two<'a>(lines_it: impl Iterator<Item = &'a str>) -> u64 {
-        let lines = lines_it.collect::<Vec<_>>();
-        let mut workflow_rules: HashMap<String, Vec<Rule>> = HashMap::new();
-        let mut workflow_ended = false;
-        let mut input_data: Vec<Input> = Vec::new();
-        for line in lines {
-            if line.is_empty() {
-                workflow_ended = true;
-                continue;
-            }
-            if !workflow_ended {
-                let (k, v) = Self::parse_workflow_data(line);
-                workflow_rules.insert(k, v);
-            } else {
-                input_data.push(Input::new(line));
-            }
-        }
+    fn solve_part_two(input: &PuzzleInput) -> u64 {
+        let (workflow_rules, _) = Self::parse_input(input);
         let workflow_manager = WorkflowManager::new(workflow_rules);
         workflow_manager
-            .find_acceptable_variable_ranges(InputBound {
-                x: (1, 4000),
-                m: (1, 4000),
-                a: (1, 4000),
-                s: (1, 4000),
-            })
+            .find_acceptable_variable_ranges(InputBound::new([Interval::inclusive(1, 4000); 4]))
             .iter()
-            .map(|range| range.count_valid_combinations())
+            .map(|range| range.volume() as u64)
             .sum()
     }
 }
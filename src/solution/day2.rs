@@ -1,6 +1,6 @@
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day2;
@@ -43,8 +43,11 @@ impl Day2 {
 }
 
 impl Solution<u32, u32> for Day2 {
-    fn part_one<'a>(games: impl Iterator<Item = &'a str>) -> u32 {
-        games
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        input
+            .lines()
             .enumerate()
             .filter(|(_, game_data)| {
                 Day2::parse_game_data(*game_data).iter().all(|match_data| {
@@ -53,8 +56,9 @@ impl Solution<u32, u32> for Day2 {
             })
             .fold(0u32, |acc, x| acc + (x.0 as u32) + 1)
     }
-    fn part_two<'a>(games: impl Iterator<Item = &'a str>) -> u32 {
-        games
+    fn solve_part_two(input: &Input) -> u32 {
+        input
+            .lines()
             .map(|game| {
                 let matches_data = Day2::parse_game_data(game);
                 let max_red = matches_data
@@ -1,7 +1,11 @@
-use num::integer;
 use std::collections::{HashMap, VecDeque};
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::animate::{self, Frame};
+use crate::utils::counters;
+use crate::utils::graph::{Graph, NodeId};
+use crate::utils::number_theory::{self, Congruence};
+use crate::utils::render::Color;
 use macros::return_type;
 
 #[return_type(p1 = u64, p2 = u64)]
@@ -18,7 +22,7 @@ struct FlipFlop {
 
 struct Conjunction {
     /// true if most recent pulse is false
-    last_pulse: HashMap<String, PulseType>,
+    last_pulse: HashMap<NodeId, PulseType>,
 }
 
 enum ModuleComponent {
@@ -27,35 +31,28 @@ enum ModuleComponent {
     Conjunction(Conjunction),
 }
 
-struct Module {
-    inner: ModuleComponent,
-    destination: Vec<String>,
-}
-
-impl Module {
-    fn receive_input(&mut self, module_from: &String, input: &PulseType) {
-        match &mut self.inner {
+impl ModuleComponent {
+    fn receive_input(&mut self, module_from: NodeId, input: &PulseType) {
+        match self {
             ModuleComponent::Conjunction(conjunction) => {
-                conjunction
-                    .last_pulse
-                    .insert(module_from.clone(), input.clone());
+                conjunction.last_pulse.insert(module_from, *input);
             }
             ModuleComponent::FlipFlop(flipflop) => {
-                flipflop.last_pulse = input.clone();
-                match input {
-                    &PulseType::HIGH => {}
-                    &PulseType::LOW => {
+                flipflop.last_pulse = *input;
+                match *input {
+                    PulseType::HIGH => {}
+                    PulseType::LOW => {
                         flipflop.is_on = !flipflop.is_on;
                     }
                 }
             }
             ModuleComponent::Broadcaster(b) => {
-                b.input.push(input.clone());
+                b.input.push(*input);
             }
         }
     }
     fn current_output(&mut self) -> Option<PulseType> {
-        match &mut self.inner {
+        match self {
             ModuleComponent::Conjunction(c) => {
                 if c.last_pulse.values().all(|x| x == &PulseType::HIGH) {
                     Some(PulseType::LOW)
@@ -85,9 +82,9 @@ impl Module {
         }
     }
     fn reset(&mut self) {
-        match &mut self.inner {
+        match self {
             ModuleComponent::Conjunction(c) => {
-                let keys = c.last_pulse.keys().map(|x| x.clone()).collect::<Vec<_>>();
+                let keys = c.last_pulse.keys().copied().collect::<Vec<_>>();
                 for key in keys {
                     c.last_pulse.insert(key, PulseType::LOW);
                 }
@@ -109,194 +106,235 @@ enum PulseType {
 }
 
 struct Machine {
-    modules: Vec<(String, Module)>,
-    module_index: HashMap<String, usize>,
+    wiring: Graph<String>,
+    /// Indexed by node id, `None` for outputs such as `rx` that only receive
+    modules: Vec<Option<ModuleComponent>>,
 }
 
 impl Machine {
-    fn new() -> Machine {
-        Machine {
-            modules: Vec::new(),
-            module_index: HashMap::new(),
+    fn new(parsed_modules: Vec<(String, ModuleComponent, Vec<String>)>) -> Machine {
+        let mut wiring = Graph::new();
+        let mut components = HashMap::new();
+        for (module_name, component, destination) in parsed_modules {
+            let id = wiring.intern(module_name);
+            for dest in destination {
+                let dest_id = wiring.intern(dest);
+                wiring.add_edge(id, dest_id, ());
+            }
+            components.insert(id, component);
         }
-    }
-    fn add_module(&mut self, module_name: String, module: Module) {
-        self.module_index
-            .insert(module_name.clone(), self.modules.len());
-        self.modules.push((module_name, module));
-    }
-    fn build_connection(&mut self) {
-        for i in 0..self.modules.len() {
-            for dest in self.modules[i].1.destination.clone().iter() {
-                let current_module_name = self.modules[i].0.clone();
-                if let Some(dest_module_index) = self.module_index.get(dest) {
-                    match &mut self.modules[*dest_module_index].1.inner {
-                        ModuleComponent::Conjunction(c) => {
-                            c.last_pulse.insert(current_module_name, PulseType::LOW);
-                        }
-                        _ => {}
-                    }
+        let mut modules = wiring
+            .nodes()
+            .map(|id| components.remove(&id))
+            .collect::<Vec<_>>();
+        for (id, module) in modules.iter_mut().enumerate() {
+            if let Some(ModuleComponent::Conjunction(c)) = module {
+                for source in wiring.predecessors(id) {
+                    c.last_pulse.insert(source, PulseType::LOW);
                 }
             }
         }
+        Machine { wiring, modules }
     }
-    fn has_module(&self, module_name: &str) -> bool {
-        self.module_index.contains_key(&String::from(module_name))
+    fn module_id(&self, module_name: &str) -> Option<NodeId> {
+        self.wiring.id(&String::from(module_name))
     }
-    fn push_button(&mut self) -> (u64, u64) {
-        let mut num_low = 1;
-        let mut num_high = 0;
-        let mut active: VecDeque<(String, PulseType, String)> = VecDeque::new();
-        for (name, _) in self.modules.iter_mut() {
-            if name == "broadcaster" {
-                active.push_back((String::new(), PulseType::LOW, name.clone()));
-            }
-        }
+    /// Presses the button once, returning every pulse a module sent out
+    /// together with the module sending it, in order.
+    fn press(&mut self) -> Vec<(NodeId, PulseType)> {
+        counters::increment("button presses");
+        let mut outputs = Vec::new();
+        let broadcaster = self.module_id("broadcaster").unwrap();
+        // The button is not a module, so the broadcaster stands in as the
+        // source of the first pulse. Only conjunctions look at the source.
+        let mut active: VecDeque<(NodeId, PulseType, NodeId)> =
+            VecDeque::from([(broadcaster, PulseType::LOW, broadcaster)]);
         while let Some((source_module, pulse_type, dest_module)) = active.pop_front() {
-            let i = *self.module_index.get(&dest_module).unwrap();
-            self.modules[i].1.receive_input(&source_module, &pulse_type);
-            let module_output = self.modules[i].1.current_output();
-            if module_output.is_none() {
+            let Some(module) = self.modules[dest_module].as_mut() else {
                 continue;
+            };
+            module.receive_input(source_module, &pulse_type);
+            let Some(module_output) = module.current_output() else {
+                continue;
+            };
+            outputs.push((dest_module, module_output));
+            for dest in self.wiring.successors(dest_module) {
+                active.push_back((dest_module, module_output, dest));
             }
-            match module_output.unwrap() {
-                PulseType::HIGH => num_high += self.modules[i].1.destination.len() as u64,
-                PulseType::LOW => num_low += self.modules[i].1.destination.len() as u64,
-            }
-            let current_module_name = self.modules[i].0.clone();
-            for dest in self.modules[i].1.destination.clone().iter() {
-                if let Some(dest_module_index) = self.module_index.get(dest) {
-                    active.push_back((
-                        current_module_name.clone(),
-                        module_output.unwrap().clone(),
-                        self.modules[*dest_module_index].0.clone(),
-                    ));
-                }
+        }
+        outputs
+    }
+    /// Presses the button for the `number`th time, returning how many low
+    /// and high pulses were sent.
+    fn push_button(&mut self, number: usize) -> (u64, u64) {
+        let mut num_low = 1;
+        let mut num_high = 0;
+        let outputs = self.press();
+        animate::frame(|| self.frame(number, &outputs));
+        for (module, pulse) in outputs {
+            match pulse {
+                PulseType::HIGH => num_high += self.wiring.out_degree(module) as u64,
+                PulseType::LOW => num_low += self.wiring.out_degree(module) as u64,
             }
         }
         (num_low, num_high)
     }
-    fn push_until_node_sends_signal(&mut self, module: &String, pulse: &PulseType) -> u64 {
+    /// Presses the button until `module` outputs `pulse`, letting that last
+    /// press settle so that pressing can resume afterwards.
+    fn push_until_node_sends_signal(&mut self, module: NodeId, pulse: &PulseType) -> u64 {
         let mut num_pushes = 0;
-        loop {
+        let mut has_sent_signal = false;
+        while !has_sent_signal {
             num_pushes += 1;
-            let mut active: VecDeque<(String, PulseType, String)> = VecDeque::new();
-            for (name, _) in self.modules.iter_mut() {
-                if name == "broadcaster" {
-                    active.push_back((String::new(), PulseType::LOW, name.clone()));
-                }
-            }
-            while let Some((source_module, pulse_type, dest_module)) = active.pop_front() {
-                let i = *self.module_index.get(&dest_module).unwrap();
-                self.modules[i].1.receive_input(&source_module, &pulse_type);
-                let module_output = self.modules[i].1.current_output();
-                if module_output.is_none() {
-                    continue;
-                }
-                if dest_module.eq(module) && module_output.unwrap() == *pulse {
-                    return num_pushes;
-                }
-                let current_module_name = self.modules[i].0.clone();
-                for dest in self.modules[i].1.destination.clone().iter() {
-                    if let Some(dest_module_index) = self.module_index.get(dest) {
-                        active.push_back((
-                            current_module_name.clone(),
-                            module_output.unwrap().clone(),
-                            self.modules[*dest_module_index].0.clone(),
-                        ));
+            has_sent_signal = self
+                .press()
+                .iter()
+                .any(|(sender, output)| *sender == module && output == pulse);
+        }
+        num_pushes
+    }
+    /// Every module with its state after the `number`th press and the
+    /// pulses it sent during it, high ones as `+` and low ones as `-`.
+    fn frame(&self, number: usize, outputs: &[(NodeId, PulseType)]) -> Frame {
+        let symbol = |pulse: &PulseType| match pulse {
+            PulseType::HIGH => ('+', Some(Color::RED)),
+            PulseType::LOW => ('-', Some(Color::BLUE)),
+        };
+        let rows = self
+            .wiring
+            .nodes()
+            .map(|id| {
+                let name = self.wiring.label(id);
+                let (kind, state) = match &self.modules[id] {
+                    Some(ModuleComponent::FlipFlop(f)) => {
+                        ('%', String::from(if f.is_on { "on" } else { "off" }))
                     }
-                }
-            }
+                    Some(ModuleComponent::Conjunction(c)) => (
+                        '&',
+                        self.wiring
+                            .predecessors(id)
+                            .map(|source| symbol(&c.last_pulse[&source]).0)
+                            .collect(),
+                    ),
+                    _ => (' ', String::new()),
+                };
+                (id, format!("{}{}", kind, name), state)
+            })
+            .collect::<Vec<_>>();
+        let name_width = rows
+            .iter()
+            .map(|(_, name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let state_width = rows
+            .iter()
+            .map(|(_, _, state)| state.len())
+            .max()
+            .unwrap_or(0);
+        let mut frame = Frame::new(format_args!("Press {}", number));
+        for (id, name, state) in rows {
+            let text = format!(
+                "{:<name_width$} {:<state_width$} ",
+                name,
+                state,
+                name_width = name_width,
+                state_width = state_width
+            );
+            frame.push_row(
+                text.chars().map(|c| (c, None)).chain(
+                    outputs
+                        .iter()
+                        .filter(|(sender, _)| *sender == id)
+                        .map(|(_, pulse)| symbol(pulse)),
+                ),
+            );
         }
+        frame
     }
     fn reset(&mut self) {
-        for (_, module) in self.modules.iter_mut() {
+        for module in self.modules.iter_mut().flatten() {
             module.reset();
         }
     }
 }
 
 impl Day20 {
-    fn parse_module(line: &str) -> (String, Module) {
+    fn parse_module(line: &str) -> (String, ModuleComponent, Vec<String>) {
         let splitted_line = line.split(" -> ").collect::<Vec<_>>();
+        let destination = splitted_line[1]
+            .split(", ")
+            .map(String::from)
+            .collect::<Vec<_>>();
         match splitted_line[0] {
             "broadcaster" => (
                 String::from(splitted_line[0]),
-                Module {
-                    destination: splitted_line[1]
-                        .split(", ")
-                        .map(String::from)
-                        .collect::<Vec<_>>(),
-                    inner: ModuleComponent::Broadcaster(Broadcaster { input: Vec::new() }),
-                },
+                ModuleComponent::Broadcaster(Broadcaster { input: Vec::new() }),
+                destination,
             ),
             _ => {
                 let is_flipflop = splitted_line[0].find('%').is_some();
                 let module_name = String::from(&splitted_line[0][1..]);
-                (
-                    module_name,
-                    Module {
-                        destination: splitted_line[1]
-                            .split(", ")
-                            .map(String::from)
-                            .collect::<Vec<_>>(),
-                        inner: if is_flipflop {
-                            ModuleComponent::FlipFlop(FlipFlop {
-                                is_on: false,
-                                last_pulse: PulseType::LOW,
-                            })
-                        } else {
-                            ModuleComponent::Conjunction(Conjunction {
-                                last_pulse: HashMap::new(),
-                            })
-                        },
-                    },
-                )
+                let component = if is_flipflop {
+                    ModuleComponent::FlipFlop(FlipFlop {
+                        is_on: false,
+                        last_pulse: PulseType::LOW,
+                    })
+                } else {
+                    ModuleComponent::Conjunction(Conjunction {
+                        last_pulse: HashMap::new(),
+                    })
+                };
+                (module_name, component, destination)
             }
         }
     }
 }
 
 impl Solution<u64, u64> for Day20 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u64 {
-        let mut machine = Machine::new();
-        for line in lines {
-            let (module_name, module) = Self::parse_module(line);
-            machine.add_module(module_name, module);
-        }
-        machine.build_connection();
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u64 {
+        let mut machine = Machine::new(input.lines().map(Self::parse_module).collect());
         let mut num_high_output = 0u64;
         let mut num_low_output = 0u64;
-        for _iter in 0..1000 {
-            let (num_low, num_high) = machine.push_button();
+        for number in 1..=1000 {
+            let (num_low, num_high) = machine.push_button(number);
             num_high_output += num_high;
             num_low_output += num_low;
         }
         num_high_output * num_low_output
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u64 {
-        let mut machine = Machine::new();
-        for line in lines {
-            let (module_name, module) = Self::parse_module(line);
-            machine.add_module(module_name, module);
-        }
-        machine.build_connection();
-        let special_modules = ["dl", "ns", "bh", "vd"];
+    fn solve_part_two(input: &Input) -> u64 {
+        let mut machine = Machine::new(input.lines().map(Self::parse_module).collect());
 
         // Sample case does not have "rx" module
-        if special_modules
-            .iter()
-            .any(|module| !machine.has_module(module))
-        {
+        let Some(rx) = machine.module_id("rx") else {
             return u64::MAX;
-        }
+        };
+        // rx is fed by a single conjunction, which sends the low pulse once
+        // all of its inputs have just sent a high pulse on the same press.
+        let feeders = machine.wiring.predecessors(rx).collect::<Vec<_>>();
+        assert!(
+            feeders.len() == 1,
+            "Expected a single module feeding rx, found {}",
+            feeders.len()
+        );
+        let special_modules = machine.wiring.predecessors(feeders[0]).collect::<Vec<_>>();
 
-        special_modules.into_iter().fold(1u64, |acc, module_name| {
-            machine.reset();
-            integer::lcm(
-                acc,
-                machine.push_until_node_sends_signal(&String::from(module_name), &PulseType::HIGH),
-            )
-        })
+        // Each special module fires on the presses congruent to its first
+        // firing modulo the gap until its second one.
+        let firings = special_modules
+            .into_iter()
+            .map(|module| {
+                machine.reset();
+                let first = machine.push_until_node_sends_signal(module, &PulseType::HIGH);
+                let gap = machine.push_until_node_sends_signal(module, &PulseType::HIGH);
+                (first, Congruence::new(first as i128, gap as i128))
+            })
+            .collect::<Vec<_>>();
+        let earliest = firings.iter().map(|(first, _)| *first).max().unwrap();
+        number_theory::crt(firings.into_iter().map(|(_, congruence)| congruence))
+            .expect("Special modules never fire on the same press")
+            .first_at_least(earliest as i128) as u64
     }
 }
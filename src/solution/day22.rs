@@ -1,6 +1,10 @@
 use std::collections::{HashSet, VecDeque};
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::animate::{self, Frame};
+use crate::utils::graph::Graph;
+use crate::utils::grid::{Grid, Pos};
+use crate::utils::render::Color;
 use macros::return_type;
 
 #[return_type(p1 = u32, p2 = u32)]
@@ -22,10 +26,13 @@ struct Block {
 struct Space3D {
     space: Vec<Vec<Vec<isize>>>,
     blocks: Vec<Block>,
-    graph: Vec<Vec<usize>>,
-    reverse_graph: Vec<Vec<isize>>,
+    /// Edges go from each block to the blocks resting on it, with `GROUND`
+    /// supporting the blocks on the floor.
+    supports: Graph<usize>,
 }
 
+const GROUND: usize = usize::MAX;
+
 impl Block {
     fn new(raw_block: &str) -> Block {
         let parsed_coordinates = raw_block
@@ -60,8 +67,7 @@ impl Space3D {
         Space3D {
             space: vec![vec![vec![-1; dim_size]; dim_size]; dim_size],
             blocks: Vec::new(),
-            graph: Vec::new(),
-            reverse_graph: Vec::new(),
+            supports: Graph::new(),
         }
     }
     fn add_block(&mut self, block: &Block) {
@@ -72,15 +78,19 @@ impl Space3D {
                 }
             }
         }
+        self.supports.intern(self.blocks.len());
         self.blocks.push(block.clone());
-        self.graph.push(Vec::new());
-        self.reverse_graph.push(Vec::new());
     }
-    fn simulate_fall(&mut self, blocks: Vec<Block>) {
+    /// Lets `blocks` fall until they rest. If `animated`, a frame shows the
+    /// bricks every time one comes to rest.
+    fn simulate_fall(&mut self, blocks: Vec<Block>, animated: bool) {
         let mut q = VecDeque::from(blocks);
         while let Some(mut block) = q.pop_front() {
             if block.lb.z == 0 {
                 self.add_block(&block);
+                if animated {
+                    animate::frame(|| self.frame(&q));
+                }
             } else {
                 let mut fallable = true;
                 for x in block.lb.x..=block.rb.x {
@@ -92,6 +102,9 @@ impl Space3D {
                 }
                 if !fallable {
                     self.add_block(&block);
+                    if animated {
+                        animate::frame(|| self.frame(&q));
+                    }
                 } else {
                     block.lb.z -= 1;
                     block.rb.z -= 1;
@@ -100,78 +113,136 @@ impl Space3D {
             }
         }
     }
-
-    fn count_disintegratable_block(&mut self) -> u32 {
-        let mut ret = 0;
-        for i in 0..self.blocks.len() {
-            if self.graph[i]
-                .iter()
-                .all(|neighbor| self.reverse_graph[*neighbor].len() >= 2)
-            {
-                ret += 1;
+    /// Side views facing along y and along x, as in the puzzle, each z
+    /// layer a row with the ground at the bottom. Bricks still `falling` are
+    /// drawn as `@`, settled ones as `#`.
+    fn frame(&self, falling: &VecDeque<Block>) -> Frame {
+        let bricks = self
+            .blocks
+            .iter()
+            .map(|block| (block, '#'))
+            .chain(falling.iter().map(|block| (block, '@')))
+            .collect::<Vec<_>>();
+        let width = bricks
+            .iter()
+            .map(|(b, _)| b.rb.x as usize + 1)
+            .max()
+            .unwrap_or(1);
+        let depth = bricks
+            .iter()
+            .map(|(b, _)| b.rb.y as usize + 1)
+            .max()
+            .unwrap_or(1);
+        let top = bricks
+            .iter()
+            .map(|(b, _)| b.rb.z as usize)
+            .max()
+            .unwrap_or(0);
+        let mut front = Grid::new(width, top + 1, '.');
+        let mut side = Grid::new(depth, top + 1, '.');
+        for (block, c) in bricks {
+            for z in block.lb.z..=block.rb.z {
+                let row = top - z as usize;
+                // Falling bricks come last, so they stay visible in front of
+                // settled ones.
+                for x in block.lb.x..=block.rb.x {
+                    front[Pos::new(row, x as usize)] = c;
+                }
+                for y in block.lb.y..=block.rb.y {
+                    side[Pos::new(row, y as usize)] = c;
+                }
             }
         }
-        ret
+        let color = |c: char| match c {
+            '#' => Some(Color::BROWN),
+            '@' => Some(Color::YELLOW),
+            _ => None,
+        };
+        let mut frame = Frame::new(format_args!(
+            "{} of {} bricks settled",
+            self.blocks.len(),
+            self.blocks.len() + falling.len()
+        ));
+        for row in 0..=top {
+            let layer = format!(" {}", top - row + 1);
+            frame.push_row(
+                front
+                    .row(row)
+                    .iter()
+                    .map(|c| (*c, color(*c)))
+                    .chain([(' ', None)])
+                    .chain(side.row(row).iter().map(|c| (*c, color(*c))))
+                    .chain(layer.chars().map(|c| (c, None))),
+            );
+        }
+        frame.push_text(
+            &format!("{} {} 0", "-".repeat(width), "-".repeat(depth)),
+            None,
+        );
+        frame
+    }
+
+    fn count_disintegratable_block(&self) -> u32 {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                let id = self.supports.id(i).unwrap();
+                self.supports
+                    .successors(id)
+                    .all(|above| self.supports.in_degree(above) >= 2)
+            })
+            .count() as u32
     }
     fn build_graph(&mut self) {
+        let ground = self.supports.intern(GROUND);
         for (i, block) in self.blocks.iter().enumerate() {
+            let id = self.supports.id(&i).unwrap();
             if block.lb.z == 0 {
+                self.supports.add_edge(ground, id, ());
                 continue;
             }
             let mut seen = HashSet::new();
             for x in block.lb.x..=block.rb.x {
                 for y in block.lb.y..=block.rb.y {
                     let curr_element = self.space[x as usize][y as usize][block.lb.z as usize - 1];
-                    if curr_element != -1 && !seen.contains(&curr_element) {
-                        self.graph[curr_element as usize].push(i);
-                        self.reverse_graph[i].push(curr_element);
-                        seen.insert(curr_element);
+                    if curr_element != -1 && seen.insert(curr_element) {
+                        let below = self.supports.id(&(curr_element as usize)).unwrap();
+                        self.supports.add_edge(below, id, ());
                     }
                 }
             }
         }
     }
-    fn find_maximum_chain(&mut self) -> u32 {
-        let mut ret = 0;
-        for i in 0..self.blocks.len() {
-            let mut indegree = self
-                .reverse_graph
-                .iter()
-                .map(|elem| elem.len())
-                .collect::<Vec<_>>();
-            let mut q = VecDeque::new();
-            q.push_back(i);
-            indegree[i] = 0;
-            while let Some(elem) = q.pop_front() {
-                ret += 1;
-                for neighbor in self.graph[elem].iter() {
-                    if indegree[*neighbor] > 0 {
-                        indegree[*neighbor] -= 1;
-                    }
-                    if indegree[*neighbor] == 0 && *neighbor != i {
-                        q.push_back(neighbor.to_owned());
-                    }
-                }
-            }
-        }
-        ret - (self.blocks.len() as u32)
+    /// A block falls when removing another exactly if every path down to the
+    /// ground goes through that other block, i.e. if it is dominated by it.
+    fn find_maximum_chain(&self) -> u32 {
+        let ground = self.supports.id(&GROUND).unwrap();
+        let dominators = self.supports.dominators(ground);
+        self.supports
+            .nodes()
+            .filter(|id| *id != ground)
+            .map(|id| dominators.strict_dominators(id).count() as u32 - 1)
+            .sum()
     }
 }
 
 impl Solution<u32, u32> for Day22 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut blocks = lines.map(Block::new).collect::<Vec<_>>();
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let mut blocks = input.lines().map(Block::new).collect::<Vec<_>>();
         blocks.sort_by(|x, y| x.lb.z.cmp(&y.lb.z));
         let mut space_3d = Space3D::new(301);
-        space_3d.simulate_fall(blocks);
+        space_3d.simulate_fall(blocks, true);
         space_3d.build_graph();
         space_3d.count_disintegratable_block()
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut blocks = lines.map(Block::new).collect::<Vec<_>>();
+    fn solve_part_two(input: &Input) -> u32 {
+        let mut blocks = input.lines().map(Block::new).collect::<Vec<_>>();
         blocks.sort_by(|x, y| x.lb.z.cmp(&y.lb.z));
         let mut space_3d = Space3D::new(301);
-        space_3d.simulate_fall(blocks);
+        space_3d.simulate_fall(blocks, false);
         space_3d.build_graph();
         space_3d.find_maximum_chain()
     }
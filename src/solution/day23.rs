@@ -1,146 +1,189 @@
-use crate::solution::Solution;
-use macros::return_type;
+use crate::solution::{Input, Solution};
+use crate::utils::counters;
+use crate::utils::direction::Direction;
+use crate::utils::graph::{self, Graph, NodeId};
+use crate::utils::grid::{Grid, Pos};
+use crate::utils::render::{self, Color, Picture};
+use macros::{memoize, return_type};
 
 #[return_type(p1 = i32, p2 = i32)]
 pub struct Day23;
 
-struct Graph {
-    grid: Vec<Vec<char>>,
-    visited: Vec<Vec<bool>>,
+struct Maze {
+    grid: Grid<char>,
+    start: Pos,
+    end: Pos,
 }
 
-const STATE: &[(isize, isize, char)] = &[(-1, 0, '^'), (0, 1, '>'), (0, -1, '<'), (1, 0, 'v')];
-const MAX_DEPTH_LIMIT: i32 = 7000;
-
-impl Graph {
-    fn new(grid: Vec<Vec<char>>) -> Graph {
-        Graph {
-            visited: vec![vec![false; grid[0].len()]; grid.len()],
+impl Maze {
+    fn new(grid: Grid<char>) -> Maze {
+        let last_row = grid.height() - 1;
+        let find_opening = |row: usize| {
+            (0..grid.width())
+                .map(|col| Pos::new(row, col))
+                .find(|pos| grid[*pos] == '.')
+                .unwrap_or_else(|| panic!("No opening found on row {}", row))
+        };
+        Maze {
+            start: find_opening(0),
+            end: find_opening(last_row),
             grid,
         }
     }
-    fn find_longest_path(&mut self, curr_pos: (isize, isize), curr_dist: i32) -> i32 {
-        if curr_pos.0 == self.grid.len() as isize - 1 {
-            return curr_dist;
+    fn open_neighbors(&self, pos: Pos) -> Vec<Pos> {
+        self.grid
+            .neighbors4(pos)
+            .filter(|npos| self.grid[*npos] != '#')
+            .collect()
+    }
+    /// Tiles reachable in one step, where slopes only allow walking downhill
+    /// if `slippery`.
+    fn next_steps(&self, pos: Pos, slippery: bool) -> Vec<Pos> {
+        match Direction::from_char(self.grid[pos]) {
+            Some(slope) if slippery => self
+                .grid
+                .step(pos, slope.delta())
+                .filter(|npos| self.grid[*npos] != '#')
+                .into_iter()
+                .collect(),
+            _ => self.open_neighbors(pos),
         }
-        self.visited[curr_pos.0 as usize][curr_pos.1 as usize] = true;
-        let mut ret = -1000000000;
-        for (dx, dy, c) in STATE.iter() {
-            if c == &self.grid[curr_pos.0 as usize][curr_pos.1 as usize]
-                || self.grid[curr_pos.0 as usize][curr_pos.1 as usize] == '.'
-            {
-                let nx = dx + curr_pos.0;
-                let ny = dy + curr_pos.1;
-                if nx < 0
-                    || nx >= self.grid.len() as isize
-                    || ny < 0
-                    || ny >= self.grid[0].len() as isize
-                {
-                    continue;
-                }
-                if !self.visited[nx as usize][ny as usize]
-                    && self.grid[nx as usize][ny as usize] != '#'
-                {
-                    ret = ret.max(self.find_longest_path((nx, ny), curr_dist + 1));
-                }
-            }
+    }
+    fn junction_graph(&self, slippery: bool) -> Graph<Pos, usize> {
+        let junctions = self
+            .grid
+            .positions()
+            .filter(|pos| self.grid[*pos] != '#' && self.open_neighbors(*pos).len() >= 3)
+            .chain([self.start, self.end]);
+        graph::contract_corridors(junctions, |pos| self.next_steps(*pos, slippery))
+    }
+    /// Longest path when slopes are slippery. They make the junction graph
+    /// acyclic, so the longest path from each junction is fixed.
+    fn longest_downhill_path(&self) -> i32 {
+        let graph = self.junction_graph(true);
+        assert!(
+            graph.topological_sort().is_some(),
+            "Slopes do not prevent walking in circles"
+        );
+        let start = graph.id(&self.start).unwrap();
+        let end = graph.id(&self.end).unwrap();
+        let len = Self::longest_dag_path_from(&graph, start, end)
+            .expect("No path found through the maze");
+        render::emit("day23-hike", || {
+            self.picture(self.downhill_hike(&graph, start, end))
+        });
+        len as i32
+    }
+    /// Tiles of the longest hike when slopes are slippery, retracing the
+    /// junctions `longest_dag_path_from` picked from its cache.
+    fn downhill_hike(&self, graph: &Graph<Pos, usize>, start: NodeId, end: NodeId) -> Vec<Pos> {
+        let mut ret = vec![self.start];
+        let mut curr = start;
+        while curr != end {
+            let (next, _) = graph
+                .out_edges(curr)
+                .iter()
+                .filter_map(|(next, len)| {
+                    Some((*next, Self::longest_dag_path_from(graph, *next, end)? + len))
+                })
+                .max_by_key(|(_, len)| *len)
+                .unwrap();
+            ret.extend(self.downhill_corridor(graph, curr, next));
+            curr = next;
         }
-        self.visited[curr_pos.0 as usize][curr_pos.1 as usize] = false;
         ret
     }
-    fn find_longest_path_optimized(
-        &mut self,
-        start: (isize, isize),
-        end: (isize, isize),
-        curr_dist: i32,
-    ) -> i32 {
-        if start == end {
-            return curr_dist * 2;
-        }
-        if curr_dist * 2 >= MAX_DEPTH_LIMIT {
-            return -1000000000;
-        }
-        let mut ret = -1000000000;
-        for (dx, dy, _) in STATE.iter() {
-            let nx = start.0 + dx;
-            let ny = start.1 + dy;
-            if nx == end.0 && ny == end.1 {
-                ret = ret.max(curr_dist * 2 + 1);
-            }
-        }
-        self.visited[start.0 as usize][start.1 as usize] = true;
-        self.visited[end.0 as usize][end.1 as usize] = true;
-        let mut start_neighbor = Vec::new();
-        let mut end_neighbor = Vec::new();
-        for (dx, dy, _) in STATE.iter() {
-            let nx = dx + start.0;
-            let ny = dy + start.1;
-            if nx < 0
-                || nx >= self.grid.len() as isize
-                || ny < 0
-                || ny >= self.grid[0].len() as isize
-            {
-                continue;
-            }
-            if !self.visited[nx as usize][ny as usize] && self.grid[nx as usize][ny as usize] != '#'
-            {
-                start_neighbor.push((nx, ny));
-            }
-        }
-        for (dx, dy, _) in STATE.iter() {
-            let nx = dx + end.0;
-            let ny = dy + end.1;
-            if nx < 0
-                || nx >= self.grid.len() as isize
-                || ny < 0
-                || ny >= self.grid[0].len() as isize
-            {
-                continue;
-            }
-            if !self.visited[nx as usize][ny as usize] && self.grid[nx as usize][ny as usize] != '#'
-            {
-                end_neighbor.push((nx, ny));
-            }
+    /// Tiles walked from junction `from` to `to`, `from` left out, along the
+    /// longest corridor between them.
+    fn downhill_corridor(&self, graph: &Graph<Pos, usize>, from: NodeId, to: NodeId) -> Vec<Pos> {
+        let from = *graph.label(from);
+        let to = *graph.label(to);
+        self.next_steps(from, true)
+            .into_iter()
+            .filter_map(|first| {
+                let (mut prev, mut tiles) = (from, vec![first]);
+                while graph.id(tiles.last().unwrap()).is_none() {
+                    let curr = *tiles.last().unwrap();
+                    let next = self
+                        .next_steps(curr, true)
+                        .into_iter()
+                        .find(|next| *next != prev)?;
+                    prev = curr;
+                    tiles.push(next);
+                }
+                Some(tiles).filter(|tiles| *tiles.last().unwrap() == to)
+            })
+            .max_by_key(|tiles| tiles.len())
+            .unwrap()
+    }
+    fn picture(&self, hike: Vec<Pos>) -> Picture {
+        let mut picture = Picture::new(&self.grid, |c| match c {
+            '#' => Color::DARK,
+            '.' => Color::GRAY,
+            _ => Color::BROWN,
+        });
+        picture.path(hike, Color::RED);
+        picture
+    }
+    #[memoize(key = (graph.graph_id(), curr, end))]
+    fn longest_dag_path_from(
+        graph: &Graph<Pos, usize>,
+        curr: NodeId,
+        end: NodeId,
+    ) -> Option<usize> {
+        counters::increment("recursive calls");
+        if curr == end {
+            return Some(0);
         }
-        for px in start_neighbor.iter() {
-            for py in end_neighbor.iter() {
-                ret = ret.max(self.find_longest_path_optimized(*px, *py, curr_dist + 1));
-            }
+        graph
+            .out_edges(curr)
+            .iter()
+            .flat_map(|(next, len)| {
+                Self::longest_dag_path_from(graph, *next, end).map(|rest| rest + len)
+            })
+            .max()
+    }
+    fn longest_path(&self) -> i32 {
+        let graph = self.junction_graph(false);
+        assert!(
+            graph.node_count() <= 64,
+            "Too many junctions found: {}",
+            graph.node_count()
+        );
+        let start = graph.id(&self.start).unwrap();
+        let end = graph.id(&self.end).unwrap();
+        Self::longest_path_from(&graph, start, end, 1 << start)
+            .expect("No path found through the maze") as i32
+    }
+    fn longest_path_from(
+        graph: &Graph<Pos, usize>,
+        curr: NodeId,
+        end: NodeId,
+        visited: u64,
+    ) -> Option<usize> {
+        counters::increment("recursive calls");
+        if curr == end {
+            return Some(0);
         }
-        self.visited[start.0 as usize][start.1 as usize] = false;
-        self.visited[end.0 as usize][end.1 as usize] = false;
-        ret
+        graph
+            .out_edges(curr)
+            .iter()
+            .filter(|(next, _)| visited & (1 << next) == 0)
+            .flat_map(|(next, len)| {
+                Self::longest_path_from(graph, *next, end, visited | (1 << next))
+                    .map(|rest| rest + len)
+            })
+            .max()
     }
 }
 
 impl Solution<i32, i32> for Day23 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> i32 {
-        let grid = lines
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        let mut graph = Graph::new(grid);
-        for i in 0..graph.grid[0].len() {
-            if graph.grid[0][i] == '.' {
-                return graph.find_longest_path((0, i as isize), 0);
-            }
-        }
-        panic!("something is wrong")
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> i32 {
+        Maze::new(input.grid::<char>()).longest_downhill_path()
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> i32 {
-        let grid = lines
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        let mut start: (isize, isize) = (0, 0);
-        let mut end: (isize, isize) = (0, 0);
-        for j in 0..grid[0].len() {
-            if grid[0][j] == '.' {
-                start = (0, j as isize);
-            }
-            if grid.last().unwrap()[j] == '.' {
-                end = (grid.len() as isize - 1, j as isize);
-            }
-        }
-        let mut graph = Graph::new(grid);
-        graph.find_longest_path_optimized(start, end, 0)
+    fn solve_part_two(input: &Input) -> i32 {
+        Maze::new(input.grid::<char>()).longest_path()
     }
 }
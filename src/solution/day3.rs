@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day3;
@@ -71,8 +71,11 @@ impl Day3 {
 }
 
 impl Solution<u32, u32> for Day3 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let lines_list = lines
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let lines_list = input
+            .lines()
             .map(|line| line.trim().chars().collect::<Vec<_>>())
             .collect::<Vec<_>>();
         let mut ret = 0;
@@ -101,8 +104,9 @@ impl Solution<u32, u32> for Day3 {
         }
         ret
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let lines_list = lines
+    fn solve_part_two(input: &Input) -> u32 {
+        let lines_list = input
+            .lines()
             .map(|line| line.trim().chars().collect::<Vec<_>>())
             .collect::<Vec<_>>();
         let mut region_mark: Vec<Vec<Option<usize>>> =
@@ -149,7 +153,6 @@ impl Solution<u32, u32> for Day3 {
                         j.try_into().unwrap(),
                         i.try_into().unwrap(),
                     );
-
                 }
             }
         }
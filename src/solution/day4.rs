@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day4;
@@ -27,8 +27,11 @@ impl Day4 {
 }
 
 impl Solution<u32, u32> for Day4 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        lines
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        input
+            .lines()
             .map(|line| Day4::parse_card_data(line))
             .fold(0u32, |acc, curr| {
                 if curr == 0 {
@@ -38,9 +41,9 @@ impl Solution<u32, u32> for Day4 {
                 }
             })
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
+    fn solve_part_two(input: &Input) -> u32 {
         let mut multiplier: Vec<u32> = Vec::new();
-        for (i, line) in lines.enumerate() {
+        for (i, line) in input.lines().enumerate() {
             while multiplier.len() <= i {
                 multiplier.push(0);
             }
@@ -1,67 +1,50 @@
-use std::collections::VecDeque;
-
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::input::numbers_in;
+use crate::solution::{Input, Solution};
+use crate::utils::interval::{Interval, IntervalSet};
 
 #[return_type(p1 = i64, p2 = i64)]
 pub struct Day5;
 
-enum QueueElement {
-    Value((i64, i64)),
-    Sentinel,
-}
-
 impl Day5 {
-    fn parse_seed_data(seed_data: &str) -> Vec<i64> {
-        seed_data
-            .split(' ')
-            .flat_map(|x| x.parse::<i64>())
-            .collect()
-    }
-    fn parse_seed_data_p2(raw_seed_data: &Vec<i64>) -> Vec<(i64, i64)> {
-        let mut ret = Vec::new();
-        let mut index = 0;
-        while index < raw_seed_data.len() {
-            ret.push((
-                raw_seed_data[index],
-                raw_seed_data[index] + raw_seed_data[index + 1] - 1,
-            ));
-            index += 2;
-        }
-        ret
+    fn parse_seed_data_p2(raw_seed_data: &[i64]) -> IntervalSet<i64> {
+        IntervalSet::from_intervals(
+            raw_seed_data
+                .chunks(2)
+                .map(|pair| Interval::with_len(pair[0], pair[1])),
+        )
     }
-    fn parse_map<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Vec<(i64, i64, i64)> {
-        let mut ret: Vec<(i64, i64, i64)> = Vec::new();
-        loop {
-            let it = lines.next();
-            if it.is_none() {
-                break;
-            }
-            let num_data = it
-                .unwrap()
-                .split(' ')
-                .flat_map(|x| x.parse::<i64>())
-                .collect::<Vec<_>>();
-            if num_data.is_empty() {
-                break;
-            }
-            assert!(num_data.len() == 3);
-            ret.push((num_data[0], num_data[1], num_data[2]));
-        }
-        lines.next();
-        ret
+    fn parse_map(section: &Input) -> Vec<(i64, i64, i64)> {
+        section
+            .numbered_lines()
+            .skip(1)
+            .map(|(line_number, line)| {
+                let num_data = numbers_in(line)
+                    .into_iter()
+                    .flat_map(|x| x.parse::<i64>())
+                    .collect::<Vec<_>>();
+                assert!(
+                    num_data.len() == 3,
+                    "Invalid mapping on line {}: {}",
+                    line_number,
+                    line
+                );
+                (num_data[0], num_data[1], num_data[2])
+            })
+            .collect()
     }
 }
 
 impl Solution<i64, i64> for Day5 {
-    fn part_one<'a>(_lines: impl Iterator<Item = &'a str>) -> i64 {
-        let lines_list = _lines.collect::<Vec<_>>();
-        let mut lines = lines_list.into_iter();
-        let seeds = Day5::parse_seed_data(lines.next().unwrap());
-        lines.nth(1).unwrap();
-        let mappings = (0..7)
-            .map(|_| Day5::parse_map(&mut lines))
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> i64 {
+        let sections = input.sections();
+        let seeds = sections[0].numbers::<i64>();
+        let mappings = sections[1..]
+            .iter()
+            .map(Day5::parse_map)
             .collect::<Vec<_>>();
         seeds.iter().fold(i64::MAX, |acc, seed_value| {
             std::cmp::min(
@@ -74,55 +57,24 @@ impl Solution<i64, i64> for Day5 {
             )
         })
     }
-    fn part_two<'a>(_lines: impl Iterator<Item = &'a str>) -> i64 {
-        let lines_list = _lines.collect::<Vec<_>>();
-        let mut lines = lines_list.into_iter();
-        let raw_seeds_data = Day5::parse_seed_data(lines.next().unwrap());
-        let mut seeds_data: Vec<(i64, i64)> = Day5::parse_seed_data_p2(&raw_seeds_data);
-        lines.nth(1).unwrap();
+    fn solve_part_two(input: &Input) -> i64 {
+        let sections = input.sections();
+        let mut seeds = Day5::parse_seed_data_p2(&sections[0].numbers::<i64>());
         let mappings: Vec<Vec<(i64, i64, i64)>> =
-            (0..7).map(|_| Day5::parse_map(&mut lines)).collect();
-        let mut remaining_ranges: VecDeque<QueueElement> = VecDeque::new();
+            sections[1..].iter().map(Day5::parse_map).collect();
         for mapping in mappings.iter() {
-            let mut new_seeds_data = Vec::new();
-            for seed_range in seeds_data.into_iter() {
-                remaining_ranges.push_back(QueueElement::Value(seed_range));
-                for mapping_range in mapping.iter() {
-                    remaining_ranges.push_back(QueueElement::Sentinel);
-                    while let Some(QueueElement::Value(curr_seed_range)) =
-                        remaining_ranges.pop_front()
-                    {
-                        if curr_seed_range.0 >= mapping_range.1 + mapping_range.2
-                            || curr_seed_range.1 < mapping_range.1
-                        {
-                            remaining_ranges.push_back(QueueElement::Value(curr_seed_range));
-                            continue;
-                        }
-                        let lb = std::cmp::max(curr_seed_range.0, mapping_range.1);
-                        let rb =
-                            std::cmp::min(curr_seed_range.1, mapping_range.1 + mapping_range.2 - 1);
-                        if lb > curr_seed_range.0 {
-                            remaining_ranges
-                                .push_back(QueueElement::Value((curr_seed_range.0, lb - 1)));
-                        }
-                        new_seeds_data.push((
-                            lb - mapping_range.1 + mapping_range.0,
-                            rb - mapping_range.1 + mapping_range.0,
-                        ));
-                        if rb < curr_seed_range.1 {
-                            remaining_ranges
-                                .push_back(QueueElement::Value((rb + 1, curr_seed_range.1)));
-                        }
-                    }
-                }
-                while let Some(QueueElement::Value(elem)) = remaining_ranges.pop_front() {
-                    new_seeds_data.push(elem);
-                }
+            let mut mapped_seeds = IntervalSet::new();
+            for (destination, source, len) in mapping.iter() {
+                let source_range = Interval::with_len(*source, *len);
+                mapped_seeds = mapped_seeds.union(
+                    &seeds
+                        .intersect_interval(&source_range)
+                        .shift(destination - source),
+                );
+                seeds = seeds.remove_interval(&source_range);
             }
-            seeds_data = Vec::from_iter(new_seeds_data.into_iter());
+            seeds = mapped_seeds.union(&seeds);
         }
-        seeds_data
-            .into_iter()
-            .fold(i64::MAX, |acc, curr| std::cmp::min(acc, curr.0))
+        seeds.min().unwrap_or(i64::MAX)
     }
 }
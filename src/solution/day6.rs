@@ -2,7 +2,7 @@ use std::iter;
 
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
 
 #[return_type(p1 = u32, p2 = i64)]
 pub struct Day6;
@@ -50,14 +50,16 @@ impl Day6 {
 }
 
 impl Solution<u32, i64> for Day6 {
-    fn part_one<'a>(_lines: impl Iterator<Item = &'a str>) -> u32 {
-        let lines = _lines.collect::<Vec<_>>();
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let lines = input.lines().collect::<Vec<_>>();
         iter::zip(Day6::parse_line(lines[0]), Day6::parse_line(lines[1])).fold(1, |acc, curr| {
             ((0..=curr.0).filter(|x| (curr.0 - x) * x > curr.1).count() as u32) * acc
         })
     }
-    fn part_two<'a>(_lines: impl Iterator<Item = &'a str>) -> i64 {
-        let lines = _lines.collect::<Vec<_>>();
+    fn solve_part_two(input: &Input) -> i64 {
+        let lines = input.lines().collect::<Vec<_>>();
         let time = Day6::join_line(Day6::parse_line(lines[0]));
         let distance = Day6::join_line(Day6::parse_line(lines[1]));
         let f = QuadraticEquation::new(-1 as f64, time as f64, -distance as f64);
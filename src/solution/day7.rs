@@ -2,7 +2,8 @@ use std::{cmp::Ordering, collections::HashMap};
 
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::trace::{event, Level};
 
 #[return_type(p1 = u32, p2 = u32)]
 pub struct Day7;
@@ -16,6 +17,18 @@ const TWO_PAIR: u32 = 3;
 const ONE_PAIR: u32 = 2;
 const HIGH_CARD: u32 = 1;
 
+fn hand_type_name(hand_type: HandType) -> &'static str {
+    match hand_type {
+        FIVE_KIND => "five of a kind",
+        FOUR_KIND => "four of a kind",
+        FULL_HOUSE => "full house",
+        THREE_KIND => "three of a kind",
+        TWO_PAIR => "two pair",
+        ONE_PAIR => "one pair",
+        _ => "high card",
+    }
+}
+
 impl Day7 {
     fn is_five_kind(card_freq: &HashMap<char, u32>) -> bool {
         card_freq.iter().any(|x| x.1 == &5)
@@ -74,16 +87,28 @@ impl Day7 {
                 ))
             })
     }
-    fn parse_hand(hand_data: &str, j_as_joker: bool) -> (HandType, &str, u32) {
+    fn parse_hand(line_number: usize, hand_data: &str, j_as_joker: bool) -> (HandType, &str, u32) {
         let splitted_hand_data = hand_data.split(' ').collect::<Vec<_>>();
+        let bid = match splitted_hand_data[..] {
+            [_, bid] => bid.parse::<u32>().ok(),
+            _ => None,
+        }
+        .unwrap_or_else(|| panic!("Invalid hand on line {}: {}", line_number, hand_data));
+        let hand_type = if j_as_joker {
+            Day7::find_best_hand(splitted_hand_data[0])
+        } else {
+            Day7::evaluate_hand(splitted_hand_data[0])
+        };
+        event!(
+            Level::Debug,
+            "{} is {}",
+            splitted_hand_data[0],
+            hand_type_name(hand_type)
+        );
         (
-            if j_as_joker {
-                Day7::find_best_hand(splitted_hand_data[0])
-            } else {
-                Day7::evaluate_hand(splitted_hand_data[0])
-            },
+            hand_type,
             splitted_hand_data[0],
-            splitted_hand_data[1].parse::<u32>().unwrap(),
+            bid,
         )
     }
     fn compare_hand(lhs: &str, rhs: &str, card_order: &Vec<char>) -> Ordering {
@@ -102,9 +127,12 @@ impl Day7 {
 }
 
 impl Solution<u32, u32> for Day7 {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut hands = lines
-            .map(|line| Day7::parse_hand(line, false))
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let mut hands = input
+            .numbered_lines()
+            .map(|(line_number, line)| Day7::parse_hand(line_number, line, false))
             .collect::<Vec<_>>();
         let card_order: Vec<char> = Vec::from([
             '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
@@ -121,9 +149,10 @@ impl Solution<u32, u32> for Day7 {
             .enumerate()
             .fold(0, |acc, curr| acc + ((curr.0 + 1) as u32) * curr.1 .2)
     }
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> u32 {
-        let mut hands = lines
-            .map(|line| Day7::parse_hand(line, true))
+    fn solve_part_two(input: &Input) -> u32 {
+        let mut hands = input
+            .numbered_lines()
+            .map(|(line_number, line)| Day7::parse_hand(line_number, line, true))
             .collect::<Vec<_>>();
         let card_order: Vec<char> = Vec::from([
             'J', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'Q', 'K', 'A',
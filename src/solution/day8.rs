@@ -1,8 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, Solution};
+use crate::utils::cycle::{self, Cycle};
+use crate::utils::graph::{Graph, NodeId};
+use crate::utils::number_theory::Congruence;
 
 #[return_type(p1 = u32, p2 = u64)]
 pub struct Day8;
@@ -20,81 +23,127 @@ impl Day8 {
             vec![intermediary_2[0].to_owned(), intermediary_2[1].to_owned()],
         )
     }
+    /// The instruction string and the network, where every node has an edge
+    /// labelled `L` and one labelled `R`.
+    fn parse_network(lines: &[&str]) -> (Vec<char>, Graph<String, char>) {
+        let instruction = lines[0].chars().collect::<Vec<_>>();
+        let mut network = Graph::new();
+        for line in lines.iter().skip(2) {
+            let (from, to) = Self::parse_edge(line);
+            network.connect(from.clone(), to[0].clone(), 'L');
+            network.connect(from, to[1].clone(), 'R');
+        }
+        (instruction, network)
+    }
+    fn next_node(network: &Graph<String, char>, node: NodeId, side: char) -> NodeId {
+        network
+            .out_edges(node)
+            .iter()
+            .find(|(_, edge_side)| *edge_side == side)
+            .map(|(next, _)| *next)
+            .unwrap_or_else(|| panic!("No {} edge found for node {}", side, network.label(node)))
+    }
     fn find_dist(
-        mut curr_node: String,
-        instruction: &Vec<char>,
-        destination_set: &HashSet<String>,
-        node_mapping: &HashMap<String, Vec<String>>,
+        mut curr_node: NodeId,
+        instruction: &[char],
+        destination_set: &HashSet<NodeId>,
+        network: &Graph<String, char>,
     ) -> u32 {
         let mut index = 0;
         let mut counter = 0;
         while !destination_set.contains(&curr_node) {
-            if instruction[index] == 'L' {
-                curr_node = node_mapping
-                    .get(&curr_node)
-                    .unwrap()
-                    .get(0)
-                    .unwrap()
-                    .clone();
-            } else {
-                curr_node = node_mapping
-                    .get(&curr_node)
-                    .unwrap()
-                    .get(1)
-                    .unwrap()
-                    .clone();
-            }
+            curr_node = Self::next_node(network, curr_node, instruction[index]);
             counter += 1;
             index = (index + 1) % instruction.len();
         }
         counter
     }
-    fn gcd(a: u64, b: u64) -> u64 {
-        if b == 0 {
-            a
-        } else {
-            Self::gcd(b, a % b)
+    /// Loop shape of the walk from `start`, together with the step indices
+    /// within its first `prefix + period` steps that land on a destination.
+    fn ghost_schedule(
+        start: NodeId,
+        instruction: &[char],
+        destination_set: &HashSet<NodeId>,
+        network: &Graph<String, char>,
+    ) -> (Cycle, Vec<usize>) {
+        let step = |(node, index): &(NodeId, usize)| {
+            (
+                Self::next_node(network, *node, instruction[*index]),
+                (index + 1) % instruction.len(),
+            )
+        };
+        let walk = cycle::find_cycle((start, 0), step);
+        let mut state = (start, 0);
+        let mut hits = Vec::new();
+        for t in 0..walk.prefix + walk.period {
+            if destination_set.contains(&state.0) {
+                hits.push(t);
+            }
+            state = step(&state);
         }
-    }
-    fn lcm(a: u64, b: u64) -> u64 {
-        a / Self::gcd(a, b) * b
+        (walk, hits)
     }
 }
 
 impl Solution<u32, u64> for Day8 {
-    fn part_one<'a>(lines_it: impl Iterator<Item = &'a str>) -> u32 {
-        let lines = lines_it.collect::<Vec<_>>();
-        let instruction = lines[0].chars().collect::<Vec<_>>();
-        let mut node_mapping: HashMap<String, Vec<String>> = HashMap::new();
-        for i in 2..lines.len() {
-            let (from, to) = Self::parse_edge(lines[i]);
-            node_mapping.insert(from, to);
-        }
-        let curr_node = String::from("AAA");
-        let mut destination_set = HashSet::new();
-        destination_set.insert(String::from("ZZZ"));
-        Self::find_dist(curr_node, &instruction, &destination_set, &node_mapping)
+    const MATERIALIZE: bool = true;
+
+    fn solve_part_one(input: &Input) -> u32 {
+        let lines = input.lines().collect::<Vec<_>>();
+        let (instruction, network) = Self::parse_network(&lines);
+        let curr_node = network.id(&String::from("AAA")).unwrap();
+        let destination_set = network.id(&String::from("ZZZ")).into_iter().collect();
+        Self::find_dist(curr_node, &instruction, &destination_set, &network)
     }
-    fn part_two<'a>(lines_it: impl Iterator<Item = &'a str>) -> u64 {
-        let lines = lines_it.collect::<Vec<_>>();
-        let instruction = lines[0].chars().collect::<Vec<_>>();
-        let mut node_mapping: HashMap<String, Vec<String>> = HashMap::new();
-        let mut destination_set: HashSet<String> = HashSet::new();
-        let mut source_list: Vec<String> = Vec::new();
-        for i in 2..lines.len() {
-            let (from, to) = Self::parse_edge(lines[i]);
-            if from.ends_with('Z') {
-                destination_set.insert(from.clone());
-            } else if from.ends_with('A') {
-                source_list.push(from.clone());
-            }
-            node_mapping.insert(from, to);
+    fn solve_part_two(input: &Input) -> u64 {
+        let lines = input.lines().collect::<Vec<_>>();
+        let (instruction, network) = Self::parse_network(&lines);
+        let destination_set: HashSet<NodeId> = network
+            .nodes()
+            .filter(|id| network.label(*id).ends_with('Z'))
+            .collect();
+        let source_list = network
+            .nodes()
+            .filter(|id| network.label(*id).ends_with('A'))
+            .collect::<Vec<_>>();
+        let schedules = source_list
+            .iter()
+            .map(|source| Self::ghost_schedule(*source, &instruction, &destination_set, &network))
+            .collect::<Vec<_>>();
+        let is_finished = |t: usize| {
+            schedules
+                .iter()
+                .all(|(walk, hits)| hits.contains(&walk.equivalent_index(t)))
+        };
+        // Before every ghost has entered its loop there is no periodic
+        // structure to exploit, so check those steps directly.
+        let settled = schedules
+            .iter()
+            .map(|(walk, _)| walk.prefix)
+            .max()
+            .unwrap_or(0);
+        if let Some(t) = (0..settled).find(|t| is_finished(*t)) {
+            return t as u64;
         }
-        source_list.into_iter().fold(1u64, |acc, curr| {
-            Self::lcm(
-                acc,
-                Self::find_dist(curr, &instruction, &destination_set, &node_mapping) as u64,
-            )
-        })
+        // Afterwards each ghost is on a destination exactly at the steps
+        // congruent to one of its looping hits, so intersect every choice.
+        schedules
+            .iter()
+            .fold(vec![Congruence::new(0, 1)], |acc, (walk, hits)| {
+                acc.iter()
+                    .flat_map(|combined| {
+                        hits.iter()
+                            .filter(|hit| **hit >= walk.prefix)
+                            .filter_map(|hit| {
+                                combined
+                                    .combine(&Congruence::new(*hit as i128, walk.period as i128))
+                            })
+                    })
+                    .collect()
+            })
+            .iter()
+            .map(|combined| combined.first_at_least(settled as i128) as u64)
+            .min()
+            .expect("Ghosts never reach destinations at the same time")
     }
 }
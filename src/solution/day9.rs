@@ -1,6 +1,7 @@
 use macros::return_type;
 
-use crate::solution::Solution;
+use crate::solution::{Input, LineStream, Solution};
+use crate::utils::polynomial;
 
 #[return_type(p1 = i32, p2 = i32)]
 pub struct Day9;
@@ -11,39 +12,39 @@ impl Day9 {
             .flat_map(|x| x.parse::<i32>())
             .collect::<Vec<_>>()
     }
-    fn extrapolate_foward(nums: &Vec<i32>) -> i32 {
-        if nums.iter().all(|x| x == &0) {
-            return 0;
-        }
-        nums.last().unwrap()
-            + Self::extrapolate_foward(
-                &(1..nums.len())
-                    .map(|index| nums[index] - nums[index - 1])
-                    .collect::<Vec<_>>(),
-            )
+    fn extrapolate_foward(nums: &[i32]) -> i32 {
+        let values = nums.iter().map(|x| *x as i128).collect::<Vec<_>>();
+        polynomial::newton_backward(&values, values.len() as i128) as i32
     }
-    fn extrapolate_backward(nums: &Vec<i32>) -> i32 {
-        if nums.iter().all(|x| x == &0) {
-            return 0;
-        }
-        nums.first().unwrap()
-            - Self::extrapolate_backward(
-                &(1..nums.len())
-                    .map(|index| nums[index] - nums[index - 1])
-                    .collect::<Vec<_>>(),
-            )
+    fn extrapolate_backward(nums: &[i32]) -> i32 {
+        let values = nums.iter().map(|x| *x as i128).collect::<Vec<_>>();
+        polynomial::newton_forward(&values, -1) as i32
     }
 }
 
 impl Solution<i32, i32> for Day9 {
-    fn part_one<'a>(lines_it: impl Iterator<Item = &'a str>) -> i32 {
-        lines_it
+    const MATERIALIZE: bool = false;
+
+    fn solve_part_one(input: &Input) -> i32 {
+        input
+            .lines()
             .map(|line| Self::extrapolate_foward(&Self::parse_line(line)))
             .sum()
     }
-    fn part_two<'a>(lines_it: impl Iterator<Item = &'a str>) -> i32 {
-        lines_it
+    fn solve_part_two(input: &Input) -> i32 {
+        input
+            .lines()
             .map(|line| Self::extrapolate_backward(&Self::parse_line(line)))
             .sum()
     }
+    fn stream_part_one(lines: LineStream) -> i32 {
+        lines
+            .map(|line| Self::extrapolate_foward(&Self::parse_line(&line)))
+            .sum()
+    }
+    fn stream_part_two(lines: LineStream) -> i32 {
+        lines
+            .map(|line| Self::extrapolate_backward(&Self::parse_line(&line)))
+            .sum()
+    }
 }
@@ -0,0 +1,150 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::utils::grid::Grid;
+
+/// Puzzle input as handed to a solution. Lines are stored with trailing
+/// whitespace (including the `\r` of Windows line endings) removed, trailing
+/// blank lines are dropped, and every line remembers its 1-based position in
+/// the original text so parsers can point at the offending line when
+/// something goes wrong.
+#[derive(Clone)]
+pub struct Input<'a> {
+    lines: Vec<&'a str>,
+    first_line: usize,
+}
+
+/// Types that can be read from a single character of a grid-shaped input.
+pub trait Cell: Sized {
+    fn from_char(c: char) -> Option<Self>;
+}
+
+impl Cell for char {
+    fn from_char(c: char) -> Option<char> {
+        Some(c)
+    }
+}
+
+impl Cell for u8 {
+    fn from_char(c: char) -> Option<u8> {
+        c.to_digit(10).map(|d| d as u8)
+    }
+}
+
+impl Cell for u32 {
+    fn from_char(c: char) -> Option<u32> {
+        c.to_digit(10)
+    }
+}
+
+impl<'a> Input<'a> {
+    pub fn new(raw: &'a str) -> Input<'a> {
+        Input::from_lines(raw.lines())
+    }
+    pub fn from_lines(lines: impl Iterator<Item = &'a str>) -> Input<'a> {
+        let mut lines = lines.map(str::trim_end).collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        Input {
+            lines,
+            first_line: 1,
+        }
+    }
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.lines.iter().copied()
+    }
+    /// Lines paired with their line number in the original input.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| (self.first_line + i, *line))
+    }
+    /// Blocks of consecutive non-blank lines. Runs of blank lines are treated
+    /// as a single separator and leading/trailing blank lines are ignored.
+    pub fn sections(&self) -> Vec<Input<'a>> {
+        let mut ret: Vec<Input<'a>> = Vec::new();
+        let mut current: Option<Input<'a>> = None;
+        for (line_number, line) in self.numbered_lines() {
+            if line.trim().is_empty() {
+                if let Some(section) = current.take() {
+                    ret.push(section);
+                }
+                continue;
+            }
+            current
+                .get_or_insert_with(|| Input {
+                    lines: Vec::new(),
+                    first_line: line_number,
+                })
+                .lines
+                .push(line);
+        }
+        if let Some(section) = current {
+            ret.push(section);
+        }
+        ret
+    }
+    pub fn grid<T: Cell>(&self) -> Grid<T> {
+        Grid::from_rows(
+            self.numbered_lines()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(line_number, line)| {
+                    line.trim_end()
+                        .chars()
+                        .map(|c| {
+                            T::from_char(c).unwrap_or_else(|| {
+                                panic!("Invalid cell {:?} found on line {}", c, line_number)
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        )
+    }
+    /// Every integer appearing in the input, in reading order.
+    pub fn numbers<T>(&self) -> Vec<T>
+    where
+        T: FromStr,
+        T::Err: Debug,
+    {
+        self.numbered_lines()
+            .flat_map(|(line_number, line)| {
+                numbers_in(line).into_iter().map(move |token| {
+                    token.parse::<T>().unwrap_or_else(|err| {
+                        panic!(
+                            "Invalid number {} on line {}: {:?}",
+                            token, line_number, err
+                        )
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+/// Splits `line` into the substrings that look like (optionally negative)
+/// integers, ignoring everything else.
+pub fn numbers_in(line: &str) -> Vec<&str> {
+    let bytes = line.as_bytes();
+    let mut ret = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && i + 1 < bytes.len()
+            && bytes[i + 1].is_ascii_digit()
+            && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric());
+        if bytes[i].is_ascii_digit() || negative {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            ret.push(&line[start..i]);
+        } else {
+            i += 1;
+        }
+    }
+    ret
+}
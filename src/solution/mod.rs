@@ -1,4 +1,172 @@
-use macros::import_all_days;
+use std::any;
+use std::fmt::Display;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::utils::counters::{self, Counts};
+use crate::utils::memo;
+use crate::utils::trace::{span, Level};
+use macros::{dispatch_day, import_all_days};
 import_all_days!();
+pub mod input;
+pub mod profile;
 pub mod solution;
+pub mod stream;
+pub use input::Input;
+pub use profile::AllocationStats;
 pub use solution::Solution;
+pub use stream::{InputSource, LineStream};
+
+#[cfg(test)]
+mod tests;
+
+/// How to run the parts, beyond solving them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Count the allocations of each part. Slows allocation down a little.
+    pub profile_allocations: bool,
+    /// Collect the counters solutions keep of the work they do.
+    pub collect_counters: bool,
+}
+
+/// Answer to one part, formatted for display, and how long it took to read
+/// the input and solve it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartRun {
+    pub answer: String,
+    pub duration: Duration,
+    /// What the part allocated, when profiling.
+    pub allocations: Option<AllocationStats>,
+    /// What the part counted, when collecting counters.
+    pub counters: Option<Counts>,
+}
+
+fn run_part<T: Display>(options: RunOptions, number: u32, part: impl FnOnce() -> T) -> PartRun {
+    let _span = span!(Level::Error, "part {}", number);
+    memo::clear_all();
+    let counted = || {
+        if options.collect_counters {
+            let (answer, counts) = counters::collect(part);
+            (answer, Some(counts))
+        } else {
+            (part(), None)
+        }
+    };
+    let start = Instant::now();
+    // Counters allocate the first time each is touched, which profiling
+    // includes when both are on.
+    let ((answer, counters), allocations) = if options.profile_allocations {
+        let (ret, stats) = profile::measure(counted);
+        (ret, Some(stats))
+    } else {
+        (counted(), None)
+    };
+    PartRun {
+        duration: start.elapsed(),
+        answer: answer.to_string(),
+        allocations,
+        counters,
+    }
+}
+
+/// Runs both parts of `S` on `source`.
+pub fn run_streamed<S, P1, P2>(
+    source: &InputSource,
+    options: RunOptions,
+) -> io::Result<[PartRun; 2]>
+where
+    S: Solution<P1, P2>,
+    P1: Display,
+    P2: Display,
+{
+    let (lines_one, lines_two) = (source.lines()?, source.lines()?);
+    Ok([
+        run_part(options, 1, || S::stream_part_one(lines_one)),
+        run_part(options, 2, || S::stream_part_two(lines_two)),
+    ])
+}
+
+/// Name of the implementation every day has. Days with alternatives can be
+/// switched to one of those in the config.
+pub const DEFAULT_IMPLEMENTATION: &str = "default";
+
+/// Like `dispatch_day!`, but picks `implementation` of the day: the default
+/// one, or one of the alternatives listed here by day and name.
+macro_rules! dispatch_implementation {
+    ($day:expr, $implementation:expr, $function:ident, $($arg:expr),*) => {
+        match ($day, $implementation) {
+            (day, DEFAULT_IMPLEMENTATION) => dispatch_day!(day, $function, $($arg),*),
+            (12, "memoized") => Some($function::<Day12Memoized, Day12P1, Day12P2>($($arg),*)),
+            _ => None,
+        }
+    };
+}
+
+/// Runs `implementation` of the solution for `day` picked at runtime, or
+/// returns `None` if there is no such implementation yet.
+pub fn run_day(
+    day: u32,
+    implementation: &str,
+    source: &InputSource,
+    options: RunOptions,
+) -> Option<io::Result<[PartRun; 2]>> {
+    dispatch_implementation!(day, implementation, run_streamed, source, options)
+}
+
+/// Runs part `part`, 1 or 2, of `S` on `text`.
+fn run_text<S, P1, P2>(text: &str, part: u32, options: RunOptions) -> PartRun
+where
+    S: Solution<P1, P2>,
+    P1: Display,
+    P2: Display,
+{
+    // The text is already in memory, so there is nothing to gain by
+    // streaming it.
+    match part {
+        1 => run_part(options, 1, || S::part_one(text.lines())),
+        2 => run_part(options, 2, || S::part_two(text.lines())),
+        _ => panic!("Invalid part {}", part),
+    }
+}
+
+/// Runs part `part`, 1 or 2, of `implementation` for `day` on `text`, or
+/// returns `None` if there is no such implementation yet.
+pub fn run_day_part(
+    day: u32,
+    implementation: &str,
+    part: u32,
+    text: &str,
+    options: RunOptions,
+) -> Option<PartRun> {
+    dispatch_implementation!(day, implementation, run_text, text, part, options)
+}
+
+/// A day with a solution, as the runtime registry lists it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisteredDay {
+    pub day: u32,
+    /// Names of the types the parts answer with, e.g. `u64`.
+    pub answer_types: [&'static str; 2],
+}
+
+fn short_type_name<T>() -> &'static str {
+    let name = any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn registered_day<S, P1, P2>(day: u32) -> RegisteredDay
+where
+    S: Solution<P1, P2>,
+{
+    RegisteredDay {
+        day,
+        answer_types: [short_type_name::<P1>(), short_type_name::<P2>()],
+    }
+}
+
+/// Every day there is a solution for, in order.
+pub fn registered_days() -> Vec<RegisteredDay> {
+    (1..=25)
+        .filter_map(|day| dispatch_day!(day, registered_day, day))
+        .collect()
+}
@@ -0,0 +1,138 @@
+//! Allocation counting for profiling the memory use of solutions.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+
+/// The system allocator, counting what the current thread allocates while
+/// inside `measure`. Outside of it the only cost is a thread-local check.
+pub struct CountingAllocator;
+
+struct Counters {
+    enabled: Cell<bool>,
+    allocations: Cell<u64>,
+    bytes: Cell<u64>,
+    live_bytes: Cell<u64>,
+    peak_bytes: Cell<u64>,
+}
+
+thread_local! {
+    // Const initialised and without a destructor, so using it never
+    // allocates, which would recurse into the allocator.
+    static COUNTERS: Counters = const {
+        Counters {
+            enabled: Cell::new(false),
+            allocations: Cell::new(0),
+            bytes: Cell::new(0),
+            live_bytes: Cell::new(0),
+            peak_bytes: Cell::new(0),
+        }
+    };
+}
+
+fn count(allocated: usize, freed: usize) {
+    let _ = COUNTERS.try_with(|counters| {
+        if !counters.enabled.get() {
+            return;
+        }
+        if allocated > 0 {
+            counters.allocations.set(counters.allocations.get() + 1);
+            counters.bytes.set(counters.bytes.get() + allocated as u64);
+        }
+        // Memory allocated before measuring started may be freed during it.
+        let live = (counters.live_bytes.get() + allocated as u64).saturating_sub(freed as u64);
+        counters.live_bytes.set(live);
+        counters.peak_bytes.set(counters.peak_bytes.get().max(live));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ret = System.alloc(layout);
+        if !ret.is_null() {
+            count(layout.size(), 0);
+        }
+        ret
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ret = System.alloc_zeroed(layout);
+        if !ret.is_null() {
+            count(layout.size(), 0);
+        }
+        ret
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        count(0, layout.size());
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let ret = System.realloc(ptr, layout, new_size);
+        if !ret.is_null() {
+            count(new_size, layout.size());
+        }
+        ret
+    }
+}
+
+/// What a measured piece of code allocated. Reallocations count as
+/// allocations of their new size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    pub allocations: u64,
+    pub bytes: u64,
+    /// Most memory allocated during the measurement and not yet freed.
+    pub peak_bytes: u64,
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < units.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
+}
+
+impl fmt::Display for AllocationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} allocated, {} peak",
+            self.allocations,
+            format_bytes(self.bytes),
+            format_bytes(self.peak_bytes)
+        )
+    }
+}
+
+/// Runs `f`, counting what it allocates on this thread. Counts are only
+/// real if `CountingAllocator` is the global allocator; otherwise they are
+/// zero.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocationStats) {
+    COUNTERS.with(|counters| {
+        for counter in [
+            &counters.allocations,
+            &counters.bytes,
+            &counters.live_bytes,
+            &counters.peak_bytes,
+        ] {
+            counter.set(0);
+        }
+        counters.enabled.set(true);
+    });
+    let ret = f();
+    let stats = COUNTERS.with(|counters| {
+        counters.enabled.set(false);
+        AllocationStats {
+            allocations: counters.allocations.get(),
+            bytes: counters.bytes.get(),
+            peak_bytes: counters.peak_bytes.get(),
+        }
+    });
+    (ret, stats)
+}
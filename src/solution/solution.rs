@@ -1,4 +1,43 @@
+use super::{Input, LineStream};
+
+/// A day's solution. Each day implements both parts on an `Input`; callers
+/// holding plain lines can use the line based `part_one`/`part_two`, which
+/// wrap the lines in an `Input` and call those.
 pub trait Solution<PartOneReturnType, PartTwoReturnType> {
-    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> PartOneReturnType;
-    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> PartTwoReturnType;
+    fn solve_part_one(input: &Input) -> PartOneReturnType;
+    fn solve_part_two(input: &Input) -> PartTwoReturnType;
+    fn part_one<'a>(lines: impl Iterator<Item = &'a str>) -> PartOneReturnType {
+        Self::solve_part_one(&Input::from_lines(lines))
+    }
+    fn part_two<'a>(lines: impl Iterator<Item = &'a str>) -> PartTwoReturnType {
+        Self::solve_part_two(&Input::from_lines(lines))
+    }
+    /// Whether the runner reads the whole input into memory for the `Input`
+    /// based methods. Days that need random access opt in with `true`; days
+    /// that only need one line at a time say `false` and override
+    /// `stream_part_one`/`stream_part_two`, which is checked when building,
+    /// to keep memory flat on huge inputs.
+    const MATERIALIZE: bool;
+    /// Entry point used by the runner, which streams the input rather than
+    /// reading it up front.
+    fn stream_part_one(lines: LineStream) -> PartOneReturnType {
+        const {
+            assert!(
+                Self::MATERIALIZE,
+                "Streaming days must implement stream_part_one"
+            )
+        };
+        let data = lines.materialize();
+        Self::solve_part_one(&Input::new(&data))
+    }
+    fn stream_part_two(lines: LineStream) -> PartTwoReturnType {
+        const {
+            assert!(
+                Self::MATERIALIZE,
+                "Streaming days must implement stream_part_two"
+            )
+        };
+        let data = lines.materialize();
+        Self::solve_part_two(&Input::new(&data))
+    }
 }
@@ -0,0 +1,146 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Puzzle input read lazily from a buffered reader, one line at a time, so
+/// a day that never looks back at earlier lines can work through inputs far
+/// larger than memory. Lines are normalized the same way as in `Input`:
+/// trailing whitespace, `\r` included, is removed and trailing blank lines
+/// are dropped.
+pub struct LineStream {
+    reader: Box<dyn BufRead>,
+    lines_read: usize,
+    /// Blank lines held back until a non-blank `lookahead` line shows they
+    /// are not trailing ones.
+    pending_blank_lines: usize,
+    lookahead: Option<String>,
+}
+
+impl LineStream {
+    pub fn new(reader: impl BufRead + 'static) -> LineStream {
+        LineStream {
+            reader: Box::new(reader),
+            lines_read: 0,
+            pending_blank_lines: 0,
+            lookahead: None,
+        }
+    }
+    #[cfg(test)]
+    pub fn from_text(text: &str) -> LineStream {
+        LineStream::new(io::Cursor::new(text.to_owned().into_bytes()))
+    }
+    pub fn open(path: &Path) -> io::Result<LineStream> {
+        Ok(LineStream::new(BufReader::new(File::open(path)?)))
+    }
+    fn read_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        let read = self.reader.read_line(&mut line).unwrap_or_else(|err| {
+            panic!("Failed to read input line {}: {}", self.lines_read + 1, err)
+        });
+        if read == 0 {
+            return None;
+        }
+        self.lines_read += 1;
+        line.truncate(line.trim_end().len());
+        Some(line)
+    }
+    /// Records separated by `delimiter` rather than by newlines, for inputs
+    /// such as day 15 that put everything on a single line. Line breaks and
+    /// surrounding whitespace are dropped and records left empty are skipped.
+    pub fn records(self, delimiter: u8) -> impl Iterator<Item = String> {
+        self.reader
+            .split(delimiter)
+            .enumerate()
+            .filter_map(|(index, record)| {
+                let mut record = record.unwrap_or_else(|err| {
+                    panic!("Failed to read input record {}: {}", index + 1, err)
+                });
+                record.retain(|b| *b != b'\n' && *b != b'\r');
+                let record = String::from_utf8(record).unwrap_or_else(|err| {
+                    panic!("Input record {} is not valid UTF-8: {}", index + 1, err)
+                });
+                let record = record.trim();
+                if record.is_empty() {
+                    return None;
+                }
+                Some(record.to_owned())
+            })
+    }
+    /// Reads the rest of the stream into memory. Days that need random access
+    /// to their input go through this to get text they can wrap in an
+    /// `Input`.
+    pub fn materialize(self) -> String {
+        let mut ret = String::new();
+        for line in self {
+            ret.push_str(&line);
+            ret.push('\n');
+        }
+        ret
+    }
+}
+
+impl Iterator for LineStream {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.pending_blank_lines > 0 {
+            self.pending_blank_lines -= 1;
+            return Some(String::new());
+        }
+        if let Some(line) = self.lookahead.take() {
+            return Some(line);
+        }
+        let mut blank_lines = 0;
+        loop {
+            let line = self.read_line()?;
+            if line.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            if blank_lines == 0 {
+                return Some(line);
+            }
+            self.pending_blank_lines = blank_lines - 1;
+            self.lookahead = Some(line);
+            return Some(String::new());
+        }
+    }
+}
+
+/// Where the runner reads a puzzle input from. Each part gets a fresh
+/// `LineStream`, so inputs that can only be read once, like stdin or an HTTP
+/// response, are spooled to a temporary file instead of being held in memory.
+pub enum InputSource {
+    File(PathBuf),
+    Spooled(PathBuf),
+}
+
+impl InputSource {
+    pub fn file(path: impl Into<PathBuf>) -> InputSource {
+        InputSource::File(path.into())
+    }
+    /// Copies `reader` to a temporary file, removed again once the source is
+    /// dropped.
+    pub fn spool(mut reader: impl Read, name: &str) -> io::Result<InputSource> {
+        let path = std::env::temp_dir().join(format!("aoc2023-{}-{}.txt", process::id(), name));
+        io::copy(&mut reader, &mut File::create(&path)?)?;
+        Ok(InputSource::Spooled(path))
+    }
+    pub fn path(&self) -> &Path {
+        match self {
+            InputSource::File(path) | InputSource::Spooled(path) => path,
+        }
+    }
+    pub fn lines(&self) -> io::Result<LineStream> {
+        LineStream::open(self.path())
+    }
+}
+
+impl Drop for InputSource {
+    fn drop(&mut self) {
+        if let InputSource::Spooled(path) = self {
+            fs::remove_file(path).ok();
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use super::*;
+
+/// The same input as saved by various editors: with Windows line endings,
+/// with trailing whitespace, with trailing blank lines, and all at once.
+fn variants(text: &str) -> Vec<(&'static str, String)> {
+    let crlf = text.replace('\n', "\r\n");
+    let padded = text.replace('\n', "  \t\n");
+    let blank_lines = format!("{}\n  \n", text);
+    let everything = format!("{}\r\n \r\n", padded.replace('\n', "\r\n"));
+    vec![
+        ("original", text.to_owned()),
+        ("crlf", crlf),
+        ("trailing whitespace", padded),
+        ("trailing blank lines", blank_lines),
+        ("crlf, whitespace and blank lines", everything),
+    ]
+}
+
+macro_rules! sample_test {
+    ($name:ident, $day:ident, $file:literal, $p1:expr, $p2:expr) => {
+        #[test]
+        fn $name() {
+            let sample = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/", $file));
+            for (variant, text) in variants(sample) {
+                assert_eq!(
+                    $day::part_one(text.lines()),
+                    $p1,
+                    "lines part one, {}",
+                    variant
+                );
+                assert_eq!(
+                    $day::part_two(text.lines()),
+                    $p2,
+                    "lines part two, {}",
+                    variant
+                );
+                let input = Input::new(&text);
+                assert_eq!($day::solve_part_one(&input), $p1, "part one, {}", variant);
+                assert_eq!($day::solve_part_two(&input), $p2, "part two, {}", variant);
+                assert_eq!(
+                    $day::stream_part_one(LineStream::from_text(&text)),
+                    $p1,
+                    "streamed part one, {}",
+                    variant
+                );
+                assert_eq!(
+                    $day::stream_part_two(LineStream::from_text(&text)),
+                    $p2,
+                    "streamed part two, {}",
+                    variant
+                );
+            }
+        }
+    };
+}
+
+sample_test!(day1, Day1, "day1.txt", 209, 281);
+sample_test!(day2, Day2, "day2.txt", 8, 2286);
+sample_test!(day3, Day3, "day3.txt", 4361, 467835);
+sample_test!(day4, Day4, "day4.txt", 13, 30);
+sample_test!(day5, Day5, "day5.txt", 35, 46);
+sample_test!(day6, Day6, "day6.txt", 288, 71503);
+sample_test!(day7, Day7, "day7.txt", 6440, 5905);
+sample_test!(day8, Day8, "day8.txt", 2, 2);
+sample_test!(day9, Day9, "day9.txt", 114, 2);
+sample_test!(day10, Day10, "day10.txt", 23, 4);
+sample_test!(day11, Day11, "day11.txt", 374, 82000210);
+sample_test!(day12, Day12, "day12.txt", 21, 525152);
+sample_test!(day12_memoized, Day12Memoized, "day12.txt", 21, 525152);
+sample_test!(day13, Day13, "day13.txt", 400, 105);
+sample_test!(day14, Day14, "day14.txt", 136, 64);
+sample_test!(day15, Day15, "day15.txt", 1320, 145);
+sample_test!(day16, Day16, "day16.txt", 46, 51);
+sample_test!(day17, Day17, "day17.txt", 102, 94);
+sample_test!(day18, Day18, "day18.txt", 62, 952408144115);
+sample_test!(day19, Day19, "day19.txt", 19114, 167409079868000);
+// The sample has no `rx` module, so part two never finishes.
+sample_test!(day20, Day20, "day20.txt", 11687500, u64::MAX);
+sample_test!(day21, Day21, "day21.txt", 42, 470149643712804);
+sample_test!(day22, Day22, "day22.txt", 5, 7);
+sample_test!(day23, Day23, "day23.txt", 94, 154);
+
+#[test]
+fn runs_day_picked_at_runtime() {
+    let sample = InputSource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day1.txt"));
+    let [one, two] = run_day(1, DEFAULT_IMPLEMENTATION, &sample, RunOptions::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!((one.answer.as_str(), two.answer.as_str()), ("209", "281"));
+    assert!(run_day(26, DEFAULT_IMPLEMENTATION, &sample, RunOptions::default()).is_none());
+    assert!(run_day(1, "brute-force", &sample, RunOptions::default()).is_none());
+
+    let sample = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day12.txt"));
+    let run = |implementation| run_day_part(12, implementation, 1, sample, RunOptions::default());
+    assert_eq!(run("memoized").unwrap().answer, "21");
+    assert_eq!(run(DEFAULT_IMPLEMENTATION).unwrap().answer, "21");
+    assert!(run("brute-force").is_none());
+}
+
+#[test]
+fn counts_allocations_of_a_part() {
+    let (len, stats) = profile::measure(|| {
+        let mut lengths = Vec::with_capacity(4);
+        let buffer = vec![0u8; 1000];
+        lengths.push(buffer.len());
+        drop(buffer);
+        lengths.push(lengths.len());
+        lengths.len()
+    });
+    assert_eq!(len, 2);
+    assert_eq!(
+        stats,
+        AllocationStats {
+            allocations: 2,
+            bytes: 1000 + 4 * 8,
+            peak_bytes: 1000 + 4 * 8,
+        }
+    );
+    assert_eq!(
+        stats.to_string(),
+        "2 allocations, 1.0 KiB allocated, 1.0 KiB peak"
+    );
+    assert_eq!(profile::measure(|| ()).1, AllocationStats::default());
+    let sample = InputSource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day22.txt"));
+    let options = RunOptions {
+        profile_allocations: true,
+        ..RunOptions::default()
+    };
+    let [one, _] = run_day(22, DEFAULT_IMPLEMENTATION, &sample, options)
+        .unwrap()
+        .unwrap();
+    assert!(one.allocations.unwrap().peak_bytes > 0);
+}
+
+#[test]
+fn explains_steps_when_tracing() {
+    use crate::utils::trace::{self, event, span, Level};
+    use std::cell::RefCell;
+
+    let sample = InputSource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day7.txt"));
+    let (runs, lines) = trace::capture(Level::Debug, || {
+        run_day(7, DEFAULT_IMPLEMENTATION, &sample, RunOptions::default())
+    });
+    assert_eq!(runs.unwrap().unwrap()[1].answer, "5905");
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], "[DEBUG] part 1 > 32T3K is one pair");
+    assert_eq!(lines[6], "[DEBUG] part 2 > T55J5 is four of a kind");
+    let ((), lines) = trace::capture(Level::Info, || {
+        let _outer = span!(Level::Info, "outer");
+        {
+            let _inner = span!(Level::Info, "inner {}", 1);
+            event!(Level::Info, "nested");
+            let formatted = RefCell::new(false);
+            let mark_formatted = || formatted.replace(true);
+            event!(Level::Debug, "{}", mark_formatted());
+            assert!(!formatted.into_inner());
+        }
+        event!(Level::Warn, "back out");
+        // A nested capture keeps its lines and gives the level back.
+        let ((), inner) = trace::capture(Level::Warn, || event!(Level::Info, "hidden"));
+        assert!(inner.is_empty());
+        event!(Level::Info, "restored");
+    });
+    assert_eq!(
+        lines,
+        [
+            "[INFO] outer > inner 1 > nested",
+            "[WARN] outer > back out",
+            "[INFO] outer > restored"
+        ]
+    );
+    assert!(!trace::enabled(Level::Error));
+}
+
+#[test]
+fn collects_counters_of_a_part() {
+    use crate::utils::counters::{self, Counts};
+    counters::increment("outside");
+    let ((), counts) = counters::collect(|| {
+        counters::increment("calls");
+        counters::add("states", 3);
+        let ((), inner) = counters::collect(|| counters::add("states", 2));
+        assert_eq!(inner, Counts::from([("states", 2)]));
+        counters::increment("calls");
+    });
+    assert_eq!(counts, Counts::from([("calls", 2), ("states", 3)]));
+    let sample = InputSource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day16.txt"));
+    let options = RunOptions {
+        collect_counters: true,
+        ..RunOptions::default()
+    };
+    let [one, two] = run_day(16, DEFAULT_IMPLEMENTATION, &sample, options)
+        .unwrap()
+        .unwrap();
+    let beam_states = |run: &PartRun| run.counters.as_ref().unwrap()["beam states"];
+    assert!(beam_states(&one) > 0);
+    assert!(beam_states(&two) > beam_states(&one));
+    let [one, _] = run_day(16, DEFAULT_IMPLEMENTATION, &sample, RunOptions::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(one.counters, None);
+}
+
+#[test]
+fn renders_pictures_of_grids() {
+    use crate::utils::grid::{Grid, Pos};
+    use crate::utils::render::{self, Color, Picture};
+    let grid = Grid::from_rows(vec![vec!['#', '.', '.'], vec!['.', '.', '#']]);
+    let mut picture = Picture::new(&grid, |c| match c {
+        '#' => Color::BLACK,
+        _ => Color::WHITE,
+    });
+    picture.fill(Pos::new(1, 0), Color(1, 2, 3));
+    picture.path(vec![Pos::new(0, 1), Pos::new(1, 1)], Color(9, 9, 9));
+    let ppm = picture.to_ppm(1);
+    assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+    let pixels = &ppm[b"P6\n3 2\n255\n".len()..];
+    assert_eq!(
+        pixels,
+        [0, 0, 0, 9, 9, 9, 255, 255, 255, 1, 2, 3, 9, 9, 9, 0, 0, 0]
+    );
+    let scaled = picture.to_ppm(4);
+    assert!(scaled.starts_with(b"P6\n12 8\n255\n"));
+    assert_eq!(scaled.len(), b"P6\n12 8\n255\n".len() + 12 * 8 * 3);
+    let svg = picture.to_svg(10);
+    assert!(svg.contains("<rect x=\"10\" y=\"0\" width=\"20\" height=\"10\" fill=\"#ffffff\"/>"));
+    assert!(svg.contains("<polyline points=\"15,5 15,15\" fill=\"none\" stroke=\"#090909\""));
+    assert!(svg.ends_with("</svg>\n"));
+
+    let dir = std::env::temp_dir().join(format!("aoc2023-test-{}-render", std::process::id()));
+    let sample = InputSource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day14.txt"));
+    render::set_output_dir(Some(dir.clone()));
+    run_day(14, DEFAULT_IMPLEMENTATION, &sample, RunOptions::default())
+        .unwrap()
+        .unwrap();
+    render::set_output_dir(None);
+    let tilted = std::fs::read(dir.join("day14-tilted.ppm")).unwrap();
+    assert!(tilted.starts_with(b"P6\n40 40\n255\n"));
+    assert!(dir.join("day14-cycled.svg").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn replays_simulations_as_frames() {
+    use crate::utils::animate::{self, Frame};
+    use crate::utils::render::Color;
+    let mut frame = Frame::new("Step 1");
+    frame.push_text("#.  ", None);
+    frame.push_row([('O', Some(Color(1, 2, 3))), ('.', None)]);
+    assert_eq!(frame.to_text(), "Step 1\n#.\nO.\n");
+    assert_eq!(
+        frame.to_ansi(),
+        "\x1b[1mStep 1\x1b[0m\n#.  \x1b[K\n\x1b[38;2;1;2;3mO\x1b[0m.\x1b[K\n"
+    );
+    // Frames only cost anything while being shown.
+    animate::frame(|| unreachable!());
+
+    let sample = InputSource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/samples/day22.txt"));
+    let (runs, frames) =
+        animate::capture(|| run_day(22, DEFAULT_IMPLEMENTATION, &sample, RunOptions::default()));
+    runs.unwrap().unwrap();
+    // The same text `--frames` dumps, so the golden file can be regenerated
+    // with it.
+    let dumped = frames
+        .iter()
+        .map(|frame| format!("{}\n", frame))
+        .collect::<String>();
+    let golden = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/samples/frames/day22.txt"
+    ));
+    assert_eq!(dumped, golden);
+}
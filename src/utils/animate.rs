@@ -0,0 +1,187 @@
+//! Step-by-step replays of simulations, played in the terminal with ANSI
+//! colors by the runner's `--animate [fps]`, or dumped as plain text with
+//! `--frames <dir>` to compare against golden files. Solutions add frames
+//! through `frame`, which does nothing unless one of those is on.
+
+use std::cell::RefCell;
+use std::fmt::{self, Write as _};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::grid::{Grid, Pos};
+use super::render::Color;
+
+/// Cells of text, each in the terminal's default color or its own.
+pub type Cell = (char, Option<Color>);
+
+/// One step of a simulation: a caption over rows of cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    caption: String,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    pub fn new(caption: impl fmt::Display) -> Frame {
+        Frame {
+            caption: caption.to_string(),
+            rows: Vec::new(),
+        }
+    }
+    /// A frame showing `grid` one cell per character.
+    pub fn from_grid<T>(
+        caption: impl fmt::Display,
+        grid: &Grid<T>,
+        cell: impl Fn(Pos, &T) -> Cell,
+    ) -> Frame {
+        let mut ret = Frame::new(caption);
+        for row in 0..grid.height() {
+            ret.push_row((0..grid.width()).map(|col| {
+                let pos = Pos::new(row, col);
+                cell(pos, &grid[pos])
+            }));
+        }
+        ret
+    }
+    /// Recolors the cell at `pos`, counting rows from the first pushed.
+    pub fn highlight(&mut self, pos: Pos, color: Color) {
+        if let Some(cell) = self
+            .rows
+            .get_mut(pos.row)
+            .and_then(|row| row.get_mut(pos.col))
+        {
+            cell.1 = Some(color);
+        }
+    }
+    pub fn push_row(&mut self, row: impl IntoIterator<Item = Cell>) {
+        self.rows.push(row.into_iter().collect());
+    }
+    pub fn push_text(&mut self, text: &str, color: Option<Color>) {
+        self.push_row(text.chars().map(|c| (c, color)));
+    }
+    /// The caption and rows without colors or trailing spaces, for
+    /// comparing with golden files.
+    pub fn to_text(&self) -> String {
+        let mut ret = format!("{}\n", self.caption);
+        for row in &self.rows {
+            let text = row.iter().map(|(c, _)| c).collect::<String>();
+            ret.push_str(text.trim_end());
+            ret.push('\n');
+        }
+        ret
+    }
+    /// The caption and rows with colors as ANSI escape codes.
+    pub fn to_ansi(&self) -> String {
+        let mut ret = format!("\x1b[1m{}\x1b[0m\n", self.caption);
+        for row in &self.rows {
+            let mut current = None;
+            for (c, color) in row {
+                if *color != current {
+                    match color {
+                        Some(Color(r, g, b)) => {
+                            write!(ret, "\x1b[38;2;{};{};{}m", r, g, b).unwrap()
+                        }
+                        None => ret.push_str("\x1b[0m"),
+                    }
+                    current = *color;
+                }
+                ret.push(*c);
+            }
+            if current.is_some() {
+                ret.push_str("\x1b[0m");
+            }
+            // Clears what is left of a longer row of the previous frame.
+            ret.push_str("\x1b[K\n");
+        }
+        ret
+    }
+}
+
+enum Sink {
+    /// Redraws each frame in place on stderr, `interval` after the last.
+    Terminal {
+        interval: Duration,
+        last: Option<Instant>,
+    },
+    File(BufWriter<File>),
+    #[cfg(test)]
+    Captured(Vec<String>),
+}
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Plays frames in the terminal at `fps` frames per second.
+pub fn play(fps: u32) {
+    let interval = Duration::from_secs(1) / fps.max(1);
+    SINK.with(|sink| {
+        *sink.borrow_mut() = Some(Sink::Terminal {
+            interval,
+            last: None,
+        })
+    });
+}
+
+/// Writes frames as text to `path`, separated by blank lines.
+pub fn dump(path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = BufWriter::new(File::create(path)?);
+    SINK.with(|sink| *sink.borrow_mut() = Some(Sink::File(file)));
+    Ok(())
+}
+
+/// Stops taking frames, finishing the file they were dumped to, if any.
+pub fn stop() -> io::Result<()> {
+    match SINK.with(|sink| sink.borrow_mut().take()) {
+        Some(Sink::File(mut file)) => file.flush(),
+        _ => Ok(()),
+    }
+}
+
+/// Shows or saves the frame `draw` makes, if animating. `draw` is only
+/// called then, so it may be slow.
+pub fn frame(draw: impl FnOnce() -> Frame) {
+    SINK.with(|sink| {
+        let mut sink = sink.borrow_mut();
+        let Some(sink) = sink.as_mut() else {
+            return;
+        };
+        let frame = draw();
+        match sink {
+            Sink::Terminal { interval, last } => {
+                match last {
+                    Some(last) => thread::sleep(interval.saturating_sub(last.elapsed())),
+                    None => eprint!("\x1b[2J"),
+                }
+                eprint!("\x1b[H{}\x1b[J", frame.to_ansi());
+                *last = Some(Instant::now());
+            }
+            Sink::File(file) => {
+                if let Err(err) = writeln!(file, "{}", frame.to_text()) {
+                    eprintln!("Could not dump a frame: {}", err);
+                }
+            }
+            #[cfg(test)]
+            Sink::Captured(frames) => frames.push(frame.to_text()),
+        }
+    })
+}
+
+/// Runs `f`, returning the text of the frames it made on this thread
+/// instead of showing them.
+#[cfg(test)]
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let previous = SINK.with(|sink| sink.replace(Some(Sink::Captured(Vec::new()))));
+    let ret = f();
+    let frames = match SINK.with(|sink| sink.replace(previous)) {
+        Some(Sink::Captured(frames)) => frames,
+        _ => Vec::new(),
+    };
+    (ret, frames)
+}
@@ -0,0 +1,37 @@
+//! Named counters of how much work a solution did, e.g. states expanded by a
+//! search, for tuning. They only count inside `collect`, which the runner
+//! uses for `--stats`; elsewhere counting is a thread-local check.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// Totals by counter name.
+pub type Counts = BTreeMap<&'static str, u64>;
+
+thread_local! {
+    static COUNTS: RefCell<Option<Counts>> = const { RefCell::new(None) };
+}
+
+/// Adds `n` to the counter `name`, if counting.
+#[inline]
+pub fn add(name: &'static str, n: u64) {
+    COUNTS.with(|counts| {
+        if let Some(counts) = counts.borrow_mut().as_mut() {
+            *counts.entry(name).or_default() += n;
+        }
+    })
+}
+
+#[inline]
+pub fn increment(name: &'static str) {
+    add(name, 1)
+}
+
+/// Runs `f`, returning what it counted on this thread. Counters never
+/// touched are left out.
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Counts) {
+    let previous = COUNTS.with(|counts| counts.replace(Some(Counts::new())));
+    let ret = f();
+    let counts = COUNTS.with(|counts| counts.replace(previous).unwrap_or_default());
+    (ret, counts)
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Shape of the sequence `start, step(start), step(step(start)), ...`: the
+/// states at indices `prefix..prefix + period` repeat forever.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Smallest index whose state equals the state at index `n`.
+    pub fn equivalent_index(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// Detects the cycle by remembering every state seen so far.
+pub fn find_cycle<S: Clone + Hash + Eq>(start: S, step: impl FnMut(&S) -> S) -> Cycle {
+    find_cycle_by_key(start, step, |state| state.clone())
+}
+
+/// Like `find_cycle`, but only remembers `fingerprint` of each state, which
+/// must be equal exactly when the states are.
+pub fn find_cycle_by_key<S, K: Hash + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> S,
+    fingerprint: impl Fn(&S) -> K,
+) -> Cycle {
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut state = start;
+    for index in 0.. {
+        if let Some(prev) = seen.insert(fingerprint(&state), index) {
+            return Cycle {
+                prefix: prev,
+                period: index - prev,
+            };
+        }
+        state = step(&state);
+    }
+    unreachable!()
+}
+
+/// Floyd's tortoise and hare. Uses constant memory but runs `step` roughly
+/// three times per state.
+pub fn floyd<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// Brent's algorithm. Uses constant memory and fewer `step` calls than
+/// `floyd`.
+pub fn brent<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+    let mut prefix = 0;
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// State after applying `step` `n` times, skipping whole periods once a
+/// repeated state is found.
+pub fn state_after<S: Clone + Hash + Eq>(start: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    state_after_by_key(start, step, |state| state.clone(), n)
+}
+
+/// Like `state_after`, but identifies repeated states by `fingerprint`.
+pub fn state_after_by_key<S, K: Hash + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> S,
+    fingerprint: impl Fn(&S) -> K,
+    n: usize,
+) -> S {
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut history = vec![start];
+    loop {
+        let index = history.len() - 1;
+        if index == n {
+            return history.pop().unwrap();
+        }
+        let state = &history[index];
+        if let Some(prev) = seen.insert(fingerprint(state), index) {
+            let cycle = Cycle {
+                prefix: prev,
+                period: index - prev,
+            };
+            return history.swap_remove(cycle.equivalent_index(n));
+        }
+        let next = step(state);
+        history.push(next);
+    }
+}
@@ -0,0 +1,100 @@
+/// Compass direction on a grid whose rows grow downwards. Variants are listed
+/// clockwise starting from `Up`, which is also the order used by `index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// Parses `UDLR`, `^>v<` and `NESW` notations.
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' | '^' | 'N' => Some(Direction::Up),
+            'R' | '>' | 'E' => Some(Direction::Right),
+            'D' | 'v' | 'S' => Some(Direction::Down),
+            'L' | '<' | 'W' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+    /// Position in clockwise order starting from `Up`, in `0..8`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+    /// Position among the four cardinal directions, in `0..4`.
+    pub fn cardinal_index(self) -> usize {
+        assert!(
+            !self.is_diagonal(),
+            "{:?} is not a cardinal direction",
+            self
+        );
+        self.index() / 2
+    }
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+    fn rotate(self, eighths: usize) -> Direction {
+        Direction::ALL[(self.index() + eighths) % 8]
+    }
+    pub fn turn_right(self) -> Direction {
+        self.rotate(2)
+    }
+    pub fn turn_left(self) -> Direction {
+        self.rotate(6)
+    }
+    pub fn turn_right_45(self) -> Direction {
+        self.rotate(1)
+    }
+    pub fn turn_left_45(self) -> Direction {
+        self.rotate(7)
+    }
+    pub fn reverse(self) -> Direction {
+        self.rotate(4)
+    }
+    /// Row/column change of a single step.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::UpRight => (-1, 1),
+            Direction::Right => (0, 1),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (1, 0),
+            Direction::DownLeft => (1, -1),
+            Direction::Left => (0, -1),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+    /// Row/column change of `steps` steps.
+    pub fn scaled(self, steps: i64) -> (i64, i64) {
+        let (d_row, d_col) = self.delta();
+        (d_row as i64 * steps, d_col as i64 * steps)
+    }
+    /// Moves a signed `(row, col)` point `steps` steps in this direction.
+    pub fn advance(self, point: (i64, i64), steps: i64) -> (i64, i64) {
+        let (d_row, d_col) = self.scaled(steps);
+        (point.0 + d_row, point.1 + d_col)
+    }
+}
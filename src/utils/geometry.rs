@@ -0,0 +1,149 @@
+use num::integer::gcd;
+
+/// Lattice point. The formulas below only care that the two axes are
+/// perpendicular, so `(x, y)` and `(row, col)` points both work; swapping
+/// axes mirrors the plane, which flips `Orientation`.
+pub type Point = (i64, i64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+fn cross(o: Point, a: Point, b: Point) -> i64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+/// Turn made when walking `a -> b -> c`, counter-clockwise meaning a left
+/// turn with the first axis pointing right and the second pointing up.
+pub fn orientation(a: Point, b: Point, c: Point) -> Orientation {
+    match cross(a, b, c).signum() {
+        1 => Orientation::CounterClockwise,
+        -1 => Orientation::Clockwise,
+        _ => Orientation::Collinear,
+    }
+}
+
+/// Checks if `p` lies on the closed segment `a-b`.
+pub fn on_segment(p: Point, a: Point, b: Point) -> bool {
+    cross(a, b, p) == 0
+        && a.0.min(b.0) <= p.0
+        && p.0 <= a.0.max(b.0)
+        && a.1.min(b.1) <= p.1
+        && p.1 <= a.1.max(b.1)
+}
+
+/// Checks if the closed segments `a-b` and `c-d` share at least one point.
+pub fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    if o1 != o2
+        && o3 != o4
+        && [o1, o2, o3, o4]
+            .iter()
+            .all(|o| *o != Orientation::Collinear)
+    {
+        return true;
+    }
+    on_segment(c, a, b) || on_segment(d, a, b) || on_segment(a, c, d) || on_segment(b, c, d)
+}
+
+/// Simple closed polygon given by its vertices in walking order. The edge from
+/// the last vertex back to the first is implied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polygon {
+    pub vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Polygon {
+        Polygon { vertices }
+    }
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+    /// Twice the signed area given by the shoelace formula, positive for
+    /// counter-clockwise polygons. Always an integer for lattice polygons.
+    pub fn signed_double_area(&self) -> i64 {
+        self.edges().map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum()
+    }
+    pub fn double_area(&self) -> i64 {
+        self.signed_double_area().abs()
+    }
+    pub fn orientation(&self) -> Orientation {
+        match self.signed_double_area().signum() {
+            1 => Orientation::CounterClockwise,
+            -1 => Orientation::Clockwise,
+            _ => Orientation::Collinear,
+        }
+    }
+    /// Number of lattice points on the edges.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|(a, b)| gcd(b.0 - a.0, b.1 - a.1)).sum()
+    }
+    /// Number of lattice points strictly inside, by Pick's theorem.
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+    pub fn locate(&self, p: Point) -> Location {
+        if self.edges().any(|(a, b)| on_segment(p, a, b)) {
+            return Location::Boundary;
+        }
+        // Cast a ray towards increasing first coordinate and count crossings,
+        // treating each edge as half-open along the second axis so vertices
+        // are not counted twice.
+        let crossings = self
+            .edges()
+            .filter(|(a, b)| (a.1 > p.1) != (b.1 > p.1))
+            .filter(|(a, b)| {
+                let side = cross(*a, *b, p);
+                if b.1 > a.1 {
+                    side > 0
+                } else {
+                    side < 0
+                }
+            })
+            .count();
+        if crossings % 2 == 1 {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+    /// Checks if any two edges touch anywhere other than the vertex shared by
+    /// neighboring edges.
+    pub fn is_self_intersecting(&self) -> bool {
+        let edges = self.edges().collect::<Vec<_>>();
+        let n = edges.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a, b) = edges[i];
+                let (c, d) = edges[j];
+                let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+                let touching = if !adjacent {
+                    segments_intersect(a, b, c, d)
+                } else if j == i + 1 {
+                    // Edges a-b and b-d only share b unless they fold back.
+                    on_segment(d, a, b) || on_segment(a, b, d)
+                } else {
+                    // Edges a-b and c-a only share a unless they fold back.
+                    on_segment(c, a, b) || on_segment(b, c, a)
+                };
+                if touching {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
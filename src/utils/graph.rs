@@ -0,0 +1,332 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub type NodeId = usize;
+
+static NEXT_GRAPH_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_graph_id() -> u64 {
+    NEXT_GRAPH_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Directed graph whose nodes are labels of type `N` (usually names or grid
+/// positions) interned to dense `NodeId`s, with edge weights of type `W`.
+#[derive(Clone, Debug)]
+pub struct Graph<N, W = ()> {
+    id: u64,
+    ids: HashMap<N, NodeId>,
+    labels: Vec<N>,
+    out_edges: Vec<Vec<(NodeId, W)>>,
+    in_edges: Vec<Vec<(NodeId, W)>>,
+}
+
+impl<N: Clone + Eq + Hash, W: Clone> Default for Graph<N, W> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N: Clone + Eq + Hash, W: Clone> Graph<N, W> {
+    pub fn new() -> Graph<N, W> {
+        Graph {
+            id: next_graph_id(),
+            ids: HashMap::new(),
+            labels: Vec::new(),
+            out_edges: Vec::new(),
+            in_edges: Vec::new(),
+        }
+    }
+    /// Id of the node labelled `label`, adding the node if it is new.
+    pub fn intern(&mut self, label: N) -> NodeId {
+        if let Some(id) = self.ids.get(&label) {
+            return *id;
+        }
+        let id = self.labels.len();
+        self.id = next_graph_id();
+        self.ids.insert(label.clone(), id);
+        self.labels.push(label);
+        self.out_edges.push(Vec::new());
+        self.in_edges.push(Vec::new());
+        id
+    }
+    /// Identifies the graph as it is now, for keying caches of results
+    /// computed from it: no other graph has the same id, and every edit
+    /// changes it. Clones share the id until either is edited.
+    pub fn graph_id(&self) -> u64 {
+        self.id
+    }
+    pub fn id(&self, label: &N) -> Option<NodeId> {
+        self.ids.get(label).copied()
+    }
+    pub fn label(&self, id: NodeId) -> &N {
+        &self.labels[id]
+    }
+    pub fn node_count(&self) -> usize {
+        self.labels.len()
+    }
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        0..self.labels.len()
+    }
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: W) {
+        self.id = next_graph_id();
+        self.out_edges[from].push((to, weight.clone()));
+        self.in_edges[to].push((from, weight));
+    }
+    pub fn add_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: W) {
+        self.add_edge(a, b, weight.clone());
+        self.add_edge(b, a, weight);
+    }
+    /// Adds an edge between two labels, interning them as needed.
+    pub fn connect(&mut self, from: N, to: N, weight: W) -> (NodeId, NodeId) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        self.add_edge(from, to, weight);
+        (from, to)
+    }
+    /// Outgoing edges in insertion order.
+    pub fn out_edges(&self, id: NodeId) -> &[(NodeId, W)] {
+        &self.out_edges[id]
+    }
+    /// Incoming edges in insertion order.
+    pub fn in_edges(&self, id: NodeId) -> &[(NodeId, W)] {
+        &self.in_edges[id]
+    }
+    pub fn successors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.out_edges[id].iter().map(|(to, _)| *to)
+    }
+    pub fn predecessors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.in_edges[id].iter().map(|(from, _)| *from)
+    }
+    pub fn out_degree(&self, id: NodeId) -> usize {
+        self.out_edges[id].len()
+    }
+    pub fn in_degree(&self, id: NodeId) -> usize {
+        self.in_edges[id].len()
+    }
+
+    /// Kahn's algorithm. Returns `None` if the graph has a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<NodeId>> {
+        let mut indegree = self
+            .nodes()
+            .map(|id| self.in_degree(id))
+            .collect::<Vec<_>>();
+        let mut q: VecDeque<NodeId> = self.nodes().filter(|id| indegree[*id] == 0).collect();
+        let mut order = Vec::with_capacity(self.node_count());
+        while let Some(curr) = q.pop_front() {
+            order.push(curr);
+            for next in self.successors(curr) {
+                indegree[next] -= 1;
+                if indegree[next] == 0 {
+                    q.push_back(next);
+                }
+            }
+        }
+        if order.len() == self.node_count() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+
+    /// Appends the nodes reachable from `root` that are not yet `visited` in
+    /// depth-first postorder, following edges backwards if `reversed`.
+    fn postorder(
+        &self,
+        root: NodeId,
+        reversed: bool,
+        visited: &mut [bool],
+        order: &mut Vec<NodeId>,
+    ) {
+        let edges = if reversed {
+            &self.in_edges
+        } else {
+            &self.out_edges
+        };
+        if visited[root] {
+            return;
+        }
+        visited[root] = true;
+        let mut stack = vec![(root, 0)];
+        while let Some((curr, index)) = stack.pop() {
+            match edges[curr].get(index) {
+                Some((next, _)) => {
+                    stack.push((curr, index + 1));
+                    if !visited[*next] {
+                        visited[*next] = true;
+                        stack.push((*next, 0));
+                    }
+                }
+                None => order.push(curr),
+            }
+        }
+    }
+
+    /// Strongly connected components (Kosaraju), listed so that every edge
+    /// between two components goes from an earlier to a later one.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut visited = vec![false; self.node_count()];
+        let mut finish_order = Vec::with_capacity(self.node_count());
+        for id in self.nodes() {
+            self.postorder(id, false, &mut visited, &mut finish_order);
+        }
+        let mut visited = vec![false; self.node_count()];
+        let mut components = Vec::new();
+        for id in finish_order.into_iter().rev() {
+            let mut component = Vec::new();
+            self.postorder(id, true, &mut visited, &mut component);
+            if !component.is_empty() {
+                components.push(component);
+            }
+        }
+        components
+    }
+
+    /// Dominator tree of the nodes reachable from `root`, using the
+    /// Cooper-Harvey-Kennedy iterative algorithm.
+    pub fn dominators(&self, root: NodeId) -> Dominators {
+        let mut order = Vec::new();
+        self.postorder(root, false, &mut vec![false; self.node_count()], &mut order);
+        order.reverse();
+        let mut rank = vec![usize::MAX; self.node_count()];
+        for (i, id) in order.iter().enumerate() {
+            rank[*id] = i;
+        }
+        let mut idom: Vec<Option<NodeId>> = vec![None; self.node_count()];
+        idom[root] = Some(root);
+        let intersect = |idom: &[Option<NodeId>], mut a: NodeId, mut b: NodeId| {
+            while a != b {
+                while rank[a] > rank[b] {
+                    a = idom[a].unwrap();
+                }
+                while rank[b] > rank[a] {
+                    b = idom[b].unwrap();
+                }
+            }
+            a
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for id in order.iter().skip(1) {
+                let new_idom = self
+                    .predecessors(*id)
+                    .filter(|pred| idom[*pred].is_some())
+                    .reduce(|acc, pred| intersect(&idom, acc, pred));
+                if new_idom != idom[*id] {
+                    idom[*id] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        Dominators { root, idom }
+    }
+
+    fn write_dot(
+        &self,
+        node_label: impl Fn(&N) -> String,
+        edge_label: impl Fn(&W) -> Option<String>,
+    ) -> String {
+        let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut ret = String::from("digraph {\n");
+        for id in self.nodes() {
+            writeln!(
+                ret,
+                "    {} [label=\"{}\"];",
+                id,
+                escape(node_label(self.label(id)))
+            )
+            .unwrap();
+        }
+        for from in self.nodes() {
+            for (to, weight) in self.out_edges(from) {
+                match edge_label(weight) {
+                    Some(label) => {
+                        writeln!(ret, "    {} -> {} [label=\"{}\"];", from, to, escape(label))
+                    }
+                    None => writeln!(ret, "    {} -> {};", from, to),
+                }
+                .unwrap();
+            }
+        }
+        ret.push_str("}\n");
+        ret
+    }
+}
+
+impl<N: Clone + Eq + Hash + Display, W: Clone> Graph<N, W> {
+    /// Graphviz DOT source for the graph, without edge weights.
+    pub fn to_dot(&self) -> String {
+        self.write_dot(|label| label.to_string(), |_| None)
+    }
+}
+
+impl<N: Clone + Eq + Hash + Display, W: Clone + Display> Graph<N, W> {
+    /// Graphviz DOT source for the graph, with edges labelled by weight.
+    pub fn to_weighted_dot(&self) -> String {
+        self.write_dot(|label| label.to_string(), |weight| Some(weight.to_string()))
+    }
+}
+
+/// Dominator tree produced by `Graph::dominators`.
+pub struct Dominators {
+    root: NodeId,
+    idom: Vec<Option<NodeId>>,
+}
+
+impl Dominators {
+    /// Closest strict dominator, or `None` for the root and unreachable nodes.
+    pub fn immediate_dominator(&self, id: NodeId) -> Option<NodeId> {
+        if id == self.root {
+            None
+        } else {
+            self.idom[id]
+        }
+    }
+    /// Strict dominators of `id`, from the closest one up to the root.
+    pub fn strict_dominators(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.immediate_dominator(id), |curr| {
+            self.immediate_dominator(*curr)
+        })
+    }
+}
+
+/// Contracts a maze into a weighted graph between `junctions`. Starting from
+/// each junction, every corridor given by `neighbors` is walked until it
+/// reaches another junction, producing an edge weighted by the corridor
+/// length. Every cell with more than two ways out must be a junction.
+/// Corridors leading to dead ends are dropped. Walks never turn
+/// straight back, so `neighbors` decides on its own whether a corridor can be
+/// taken in both directions.
+pub fn contract_corridors<N: Clone + Eq + Hash>(
+    junctions: impl IntoIterator<Item = N>,
+    neighbors: impl Fn(&N) -> Vec<N>,
+) -> Graph<N, usize> {
+    let mut graph = Graph::new();
+    let junctions = junctions
+        .into_iter()
+        .inspect(|junction| {
+            graph.intern(junction.clone());
+        })
+        .collect::<HashSet<_>>();
+    for junction in graph.labels.clone() {
+        for first in neighbors(&junction) {
+            let (mut prev, mut curr, mut len) = (junction.clone(), first, 1);
+            while !junctions.contains(&curr) {
+                let next = neighbors(&curr).into_iter().find(|next| *next != prev);
+                match next {
+                    Some(next) => {
+                        prev = std::mem::replace(&mut curr, next);
+                        len += 1;
+                    }
+                    None => break,
+                }
+            }
+            if junctions.contains(&curr) {
+                graph.connect(junction.clone(), curr, len);
+            }
+        }
+    }
+    graph
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Row/column deltas of the four orthogonal neighbors, clockwise from up.
+pub const NEIGHBORS_4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Row/column deltas of all eight surrounding cells, clockwise from up.
+pub const NEIGHBORS_8: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Pos {
+        Pos { row, col }
+    }
+    /// Moves by `(d_row, d_col)`, returning `None` if either coordinate would
+    /// become negative.
+    pub fn offset(&self, delta: (isize, isize)) -> Option<Pos> {
+        Some(Pos {
+            row: self.row.checked_add_signed(delta.0)?,
+            col: self.col.checked_add_signed(delta.1)?,
+        })
+    }
+    pub fn manhattan(&self, other: &Pos) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+/// Rectangular grid stored row-major in a single `Vec`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "All rows of a grid must have the same length"
+        );
+        Grid {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn in_bounds(&self, pos: Pos) -> bool {
+        pos.row < self.height && pos.col < self.width
+    }
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.in_bounds(pos) {
+            Some(&self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.in_bounds(pos) {
+            Some(&mut self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+    /// Looks up a cell on the grid repeated infinitely in every direction.
+    pub fn get_wrapping(&self, row: isize, col: isize) -> &T {
+        let row = row.rem_euclid(self.height as isize) as usize;
+        let col = col.rem_euclid(self.width as isize) as usize;
+        &self.cells[row * self.width + col]
+    }
+    /// Moves `pos` by `delta`, returning `None` if the result leaves the grid.
+    pub fn step(&self, pos: Pos, delta: (isize, isize)) -> Option<Pos> {
+        pos.offset(delta).filter(|next| self.in_bounds(*next))
+    }
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBORS_4
+            .iter()
+            .flat_map(move |delta| self.step(pos, *delta))
+    }
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBORS_8
+            .iter()
+            .flat_map(move |delta| self.step(pos, *delta))
+    }
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| Pos::new(row, col)))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+    pub fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> {
+        self.cells.iter().skip(col).step_by(self.width.max(1))
+    }
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+    pub fn transpose(&self) -> Grid<T> {
+        Grid::from_rows(
+            self.columns()
+                .map(|column| column.cloned().collect())
+                .collect(),
+        )
+    }
+    pub fn rotate_clockwise(&self) -> Grid<T> {
+        Grid::from_rows(
+            (0..self.width)
+                .map(|col| self.column(col).rev().cloned().collect())
+                .collect(),
+        )
+    }
+    pub fn rotate_counterclockwise(&self) -> Grid<T> {
+        Grid::from_rows(
+            (0..self.width)
+                .rev()
+                .map(|col| self.column(col).cloned().collect())
+                .collect(),
+        )
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos: Pos) -> &T {
+        assert!(
+            self.in_bounds(pos),
+            "Position {:?} is outside the grid",
+            pos
+        );
+        &self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        assert!(
+            self.in_bounds(pos),
+            "Position {:?} is outside the grid",
+            pos
+        );
+        &mut self.cells[pos.row * self.width + pos.col]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
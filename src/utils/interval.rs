@@ -0,0 +1,179 @@
+use num::PrimInt;
+
+/// Half-open integer interval `[start, end)`. Any interval with
+/// `start >= end` is empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    pub fn new(start: T, end: T) -> Interval<T> {
+        Interval { start, end }
+    }
+    /// Interval covering `first..=last`.
+    pub fn inclusive(first: T, last: T) -> Interval<T> {
+        Interval {
+            start: first,
+            end: last + T::one(),
+        }
+    }
+    /// Interval of `len` values starting at `start`.
+    pub fn with_len(start: T, len: T) -> Interval<T> {
+        Interval {
+            start,
+            end: start + len,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.end - self.start
+        }
+    }
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let ret = Interval::new(self.start.max(other.start), self.end.min(other.end));
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+    /// Parts of `self` not covered by `other`, in increasing order.
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> {
+        if self.intersection(other).is_none() {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        }
+        [
+            Interval::new(self.start, other.start.min(self.end)),
+            Interval::new(other.end.max(self.start), self.end),
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect()
+    }
+    /// Splits into the values below `point` and the values from `point` on.
+    pub fn split_at(&self, point: T) -> (Option<Interval<T>>, Option<Interval<T>>) {
+        let below = Interval::new(self.start, point.min(self.end));
+        let above = Interval::new(point.max(self.start), self.end);
+        (
+            Some(below).filter(|part| !part.is_empty()),
+            Some(above).filter(|part| !part.is_empty()),
+        )
+    }
+    pub fn shift(&self, delta: T) -> Interval<T> {
+        Interval::new(self.start + delta, self.end + delta)
+    }
+}
+
+/// Union of disjoint intervals, kept sorted with touching intervals merged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+    pub fn from_intervals(intervals: impl IntoIterator<Item = Interval<T>>) -> IntervalSet<T> {
+        let mut ret = IntervalSet::new();
+        for interval in intervals {
+            ret.insert(interval);
+        }
+        ret
+    }
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        let mut merged = interval;
+        let mut kept = Vec::with_capacity(self.intervals.len() + 1);
+        for curr in self.intervals.drain(..) {
+            if curr.end < merged.start || merged.end < curr.start {
+                kept.push(curr);
+            } else {
+                merged = Interval::new(merged.start.min(curr.start), merged.end.max(curr.end));
+            }
+        }
+        kept.push(merged);
+        kept.sort_by_key(|curr| curr.start);
+        self.intervals = kept;
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|curr| curr.start)
+    }
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        IntervalSet::from_intervals(self.iter().chain(other.iter()).copied())
+    }
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        IntervalSet::from_intervals(
+            self.iter()
+                .flat_map(|lhs| other.iter().flat_map(|rhs| lhs.intersection(rhs))),
+        )
+    }
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut remaining = self.intervals.clone();
+        for removed in other.iter() {
+            remaining = remaining
+                .iter()
+                .flat_map(|curr| curr.difference(removed))
+                .collect();
+        }
+        IntervalSet::from_intervals(remaining)
+    }
+    pub fn intersect_interval(&self, interval: &Interval<T>) -> IntervalSet<T> {
+        self.intersection(&IntervalSet::from_intervals([*interval]))
+    }
+    pub fn remove_interval(&self, interval: &Interval<T>) -> IntervalSet<T> {
+        self.difference(&IntervalSet::from_intervals([*interval]))
+    }
+    pub fn shift(&self, delta: T) -> IntervalSet<T> {
+        IntervalSet::from_intervals(self.iter().map(|curr| curr.shift(delta)))
+    }
+}
+
+/// Axis-aligned box in `N` dimensions, the cartesian product of one interval
+/// per axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalBox<T, const N: usize> {
+    pub axes: [Interval<T>; N],
+}
+
+impl<T: PrimInt, const N: usize> IntervalBox<T, N> {
+    pub fn new(axes: [Interval<T>; N]) -> IntervalBox<T, N> {
+        IntervalBox { axes }
+    }
+    /// Number of integer points inside the box.
+    pub fn volume(&self) -> u128 {
+        self.axes
+            .iter()
+            .map(|axis| axis.len().to_u128().unwrap())
+            .product()
+    }
+    /// Splits along `axis` into the part below `point` and the part from
+    /// `point` on.
+    pub fn split_at(
+        &self,
+        axis: usize,
+        point: T,
+    ) -> (Option<IntervalBox<T, N>>, Option<IntervalBox<T, N>>) {
+        let (below, above) = self.axes[axis].split_at(point);
+        let with_axis = |interval: Interval<T>| {
+            let mut axes = self.axes;
+            axes[axis] = interval;
+            IntervalBox { axes }
+        };
+        (below.map(with_axis), above.map(with_axis))
+    }
+}
@@ -0,0 +1,94 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::counters;
+
+trait Cache {
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<K: Hash + Eq + 'static, V: 'static> Cache for HashMap<K, V> {
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+    fn clear(&mut self) {
+        HashMap::clear(self)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+thread_local! {
+    static CACHES: RefCell<HashMap<&'static str, Box<dyn Cache>>> = RefCell::new(HashMap::new());
+}
+
+fn with_cache<K, V, R>(name: &'static str, f: impl FnOnce(&mut HashMap<K, V>) -> R) -> R
+where
+    K: Hash + Eq + 'static,
+    V: 'static,
+{
+    CACHES.with(|caches| {
+        let mut caches = caches.borrow_mut();
+        let cache = caches
+            .entry(name)
+            .or_insert_with(|| Box::new(HashMap::<K, V>::new()));
+        let cache = cache
+            .as_any_mut()
+            .downcast_mut::<HashMap<K, V>>()
+            .unwrap_or_else(|| panic!("Cache {} used with different key or value types", name));
+        f(cache)
+    })
+}
+
+/// Looks up a result of the memoized function `name`. Together with `insert`,
+/// this backs the `#[memoize]` attribute from the `macros` crate, which gives
+/// every memoized function its own cache named after the function's path.
+pub fn get<K, V>(name: &'static str, key: &K) -> Option<V>
+where
+    K: Hash + Eq + 'static,
+    V: Clone + 'static,
+{
+    let ret = with_cache(name, |cache: &mut HashMap<K, V>| cache.get(key).cloned());
+    if ret.is_some() {
+        counters::increment("memo hits");
+    }
+    ret
+}
+
+pub fn insert<K, V>(name: &'static str, key: K, value: V)
+where
+    K: Hash + Eq + 'static,
+    V: 'static,
+{
+    with_cache(name, |cache| {
+        cache.insert(key, value);
+    })
+}
+
+/// Number of results currently cached for `name`.
+pub fn size(name: &str) -> usize {
+    CACHES.with(|caches| caches.borrow().get(name).map_or(0, |cache| cache.len()))
+}
+
+pub fn clear(name: &str) {
+    CACHES.with(|caches| {
+        if let Some(cache) = caches.borrow_mut().get_mut(name) {
+            cache.clear();
+        }
+    })
+}
+
+/// Empties every cache. The runner does this before each part, so results
+/// computed for one input are never reused for another.
+pub fn clear_all() {
+    CACHES.with(|caches| {
+        for cache in caches.borrow_mut().values_mut() {
+            cache.clear();
+        }
+    })
+}
@@ -0,0 +1,26 @@
+// Toolkits marked `allow(dead_code)` keep a complete, tested API even where
+// no day needs all of it.
+pub mod animate;
+pub mod counters;
+#[allow(dead_code)]
+pub mod cycle;
+#[allow(dead_code)]
+pub mod direction;
+#[allow(dead_code)]
+pub mod geometry;
+#[allow(dead_code)]
+pub mod graph;
+#[allow(dead_code)]
+pub mod grid;
+pub mod interval;
+pub mod memo;
+#[allow(dead_code)]
+pub mod number_theory;
+pub mod polynomial;
+pub mod render;
+#[allow(dead_code)]
+pub mod search;
+pub mod trace;
+
+#[cfg(test)]
+mod tests;
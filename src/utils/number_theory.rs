@@ -0,0 +1,95 @@
+/// Extended Euclidean algorithm. Returns `(g, x, y)` with `g = gcd(a, b) >= 0`
+/// and `a * x + b * y = g`.
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        if a < 0 {
+            (-a, -1, 0)
+        } else {
+            (a, 1, 0)
+        }
+    } else {
+        let (g, x, y) = ext_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+/// Inverse of `a` modulo `m`, in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    if g == 1 {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+/// `a * b mod m` for `a, b` in `0..m`, falling back to double-and-add when the
+/// plain product would overflow.
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product.rem_euclid(m);
+    }
+    let (mut a, mut b, mut ret) = (a, b, 0i128);
+    while b > 0 {
+        if b & 1 == 1 {
+            ret = (ret + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    ret
+}
+
+/// The set of integers `x` with `x = residue (mod modulus)`, kept normalized
+/// so that `residue` lies in `0..modulus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Congruence {
+    pub residue: i128,
+    pub modulus: i128,
+}
+
+impl Congruence {
+    pub fn new(residue: i128, modulus: i128) -> Congruence {
+        assert!(modulus > 0, "Invalid modulus: {}", modulus);
+        Congruence {
+            residue: residue.rem_euclid(modulus),
+            modulus,
+        }
+    }
+    /// Intersection of both solution sets, or `None` if they are disjoint.
+    /// The moduli need not be coprime.
+    pub fn combine(&self, other: &Congruence) -> Option<Congruence> {
+        let (g, p, _) = ext_gcd(self.modulus, other.modulus);
+        let diff = other.residue - self.residue;
+        if diff % g != 0 {
+            return None;
+        }
+        let other_reduced = other.modulus / g;
+        let modulus = (self.modulus / g)
+            .checked_mul(other.modulus)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Combined modulus of {} and {} overflows",
+                    self.modulus, other.modulus
+                )
+            });
+        let k = mul_mod(
+            (diff / g).rem_euclid(other_reduced),
+            p.rem_euclid(other_reduced),
+            other_reduced,
+        );
+        Some(Congruence::new(self.residue + self.modulus * k, modulus))
+    }
+    /// Smallest solution that is at least `bound`.
+    pub fn first_at_least(&self, bound: i128) -> i128 {
+        bound + (self.residue - bound).rem_euclid(self.modulus)
+    }
+}
+
+/// Generalized Chinese Remainder Theorem: the common solutions of all
+/// congruences, or `None` if there are none.
+pub fn crt(congruences: impl IntoIterator<Item = Congruence>) -> Option<Congruence> {
+    congruences
+        .into_iter()
+        .try_fold(Congruence::new(0, 1), |acc, curr| acc.combine(&curr))
+}
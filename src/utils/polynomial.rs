@@ -0,0 +1,129 @@
+use num::rational::Ratio;
+use num::{One, Zero};
+
+pub type Rational = Ratio<i128>;
+
+/// Rows of successive differences, starting with `values` itself and ending
+/// at the first row that is all zeros (or empty).
+pub fn difference_table(values: &[i128]) -> Vec<Vec<i128>> {
+    let mut table = vec![values.to_vec()];
+    while table.last().unwrap().iter().any(|x| *x != 0) {
+        let row = table.last().unwrap();
+        table.push(row.windows(2).map(|pair| pair[1] - pair[0]).collect());
+    }
+    table
+}
+
+/// Lowest degree `d` such that the values, taken at consecutive points, lie
+/// on a polynomial of degree `d`, provided that at least `confirmations`
+/// of the `(d + 1)`-th differences are available to back it up.
+pub fn detect_degree(values: &[i128], confirmations: usize) -> Option<usize> {
+    let table = difference_table(values);
+    let zeros = table.last().unwrap();
+    if zeros.len() < confirmations.max(1) {
+        return None;
+    }
+    Some(table.len().saturating_sub(2))
+}
+
+/// `x` choose `k` for any integer `x`, including negative ones.
+fn binomial(x: i128, k: usize) -> i128 {
+    let mut ret = 1i128;
+    for j in 0..k as i128 {
+        // Every prefix of the product is itself a binomial coefficient, so
+        // the division is exact.
+        ret = ret * (x - j) / (j + 1);
+    }
+    ret
+}
+
+/// Newton forward interpolation: evaluates the polynomial through `values`,
+/// taken at `x = 0, 1, 2, ...`, at `x`.
+pub fn newton_forward(values: &[i128], x: i128) -> i128 {
+    difference_table(values)
+        .iter()
+        .enumerate()
+        .filter_map(|(k, row)| row.first().map(|first| first * binomial(x, k)))
+        .sum()
+}
+
+/// Newton backward interpolation: like `newton_forward` but built from the
+/// differences at the last value, which suits extrapolating past the end.
+pub fn newton_backward(values: &[i128], x: i128) -> i128 {
+    let t = x - (values.len() as i128 - 1);
+    difference_table(values)
+        .iter()
+        .enumerate()
+        .filter_map(|(k, row)| row.last().map(|last| last * binomial(t + k as i128 - 1, k)))
+        .sum()
+}
+
+/// Polynomial with exact rational coefficients, lowest degree first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial {
+    coefficients: Vec<Rational>,
+}
+
+impl Polynomial {
+    pub fn new(coefficients: Vec<Rational>) -> Polynomial {
+        let mut ret = Polynomial { coefficients };
+        while ret.coefficients.last().is_some_and(|c| c.is_zero()) {
+            ret.coefficients.pop();
+        }
+        ret
+    }
+    /// Lagrange interpolation through points with distinct `x`.
+    pub fn lagrange(points: &[(i128, i128)]) -> Polynomial {
+        let mut coefficients = vec![Rational::zero(); points.len()];
+        for (i, (xi, yi)) in points.iter().enumerate() {
+            // Expand yi * prod (x - xj) / (xi - xj) one factor at a time.
+            let mut basis = vec![Rational::from_integer(*yi)];
+            for (j, (xj, _)) in points.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                assert!(xi != xj, "Duplicate x found in interpolation: {}", xi);
+                let scale = Rational::new(1, xi - xj);
+                let mut next = vec![Rational::zero(); basis.len() + 1];
+                for (k, c) in basis.iter().enumerate() {
+                    next[k + 1] += c * scale;
+                    next[k] -= c * scale * xj;
+                }
+                basis = next;
+            }
+            for (k, c) in basis.into_iter().enumerate() {
+                coefficients[k] += c;
+            }
+        }
+        Polynomial::new(coefficients)
+    }
+    /// Fits the values taken at `x = 0, 1, 2, ...` with a polynomial of the
+    /// detected degree, if at least `confirmations` differences back it.
+    pub fn fit(values: &[i128], confirmations: usize) -> Option<Polynomial> {
+        let degree = detect_degree(values, confirmations)?;
+        let points = values
+            .iter()
+            .take(degree + 1)
+            .enumerate()
+            .map(|(x, y)| (x as i128, *y))
+            .collect::<Vec<_>>();
+        Some(Polynomial::lagrange(&points))
+    }
+    pub fn eval(&self, x: &Rational) -> Rational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Rational::zero(), |acc, c| acc * x + c)
+    }
+    /// Evaluates at an integer point where the value is known to be integral.
+    pub fn eval_integer(&self, x: i128) -> i128 {
+        let ret = self.eval(&Rational::from_integer(x));
+        assert!(
+            ret.denom().is_one(),
+            "Polynomial value at {} is not an integer: {}",
+            x,
+            ret
+        );
+        ret.to_integer()
+    }
+}
@@ -0,0 +1,195 @@
+//! Pictures of grids for looking at what a solution did, written as PPM and
+//! SVG files. Solutions draw them through `emit`, which does nothing unless
+//! the runner was given `--render <dir>`.
+
+use std::cell::RefCell;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::grid::{Grid, Pos};
+
+/// Pixels per cell in rendered files.
+pub const CELL_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const WHITE: Color = Color(255, 255, 255);
+    pub const GRAY: Color = Color(128, 128, 128);
+    pub const DARK: Color = Color(32, 32, 40);
+    pub const RED: Color = Color(220, 50, 47);
+    pub const GREEN: Color = Color(64, 160, 43);
+    pub const BLUE: Color = Color(38, 139, 210);
+    pub const YELLOW: Color = Color(250, 200, 40);
+    pub const BROWN: Color = Color(140, 90, 50);
+
+    /// The color `t` of the way from `self` to `other`.
+    pub fn mix(self, other: Color, t: f64) -> Color {
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// A grid of colored cells with paths drawn over them through the cell
+/// centers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Picture {
+    cells: Grid<Color>,
+    paths: Vec<(Vec<Pos>, Color)>,
+}
+
+impl Picture {
+    pub fn new<T>(grid: &Grid<T>, color: impl Fn(&T) -> Color) -> Picture {
+        Picture {
+            cells: grid.map(color),
+            paths: Vec::new(),
+        }
+    }
+    pub fn fill(&mut self, pos: Pos, color: Color) {
+        self.cells[pos] = color;
+    }
+    /// Draws a line through `points` in order. Repeat the first point at the
+    /// end to close a loop.
+    pub fn path(&mut self, points: Vec<Pos>, color: Color) {
+        self.paths.push((points, color));
+    }
+    /// Binary PPM with `scale` pixels per cell.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.cells.width() * scale, self.cells.height() * scale);
+        let mut pixels = Grid::new(width, height, Color::BLACK);
+        for (pos, color) in self.cells.iter() {
+            for row in pos.row * scale..(pos.row + 1) * scale {
+                for col in pos.col * scale..(pos.col + 1) * scale {
+                    pixels[Pos::new(row, col)] = *color;
+                }
+            }
+        }
+        let center = |pos: &Pos| Pos::new(pos.row * scale + scale / 2, pos.col * scale + scale / 2);
+        for (points, color) in &self.paths {
+            for pair in points.windows(2) {
+                for pos in line(center(&pair[0]), center(&pair[1])) {
+                    pixels[pos] = *color;
+                }
+            }
+        }
+        let mut ret = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for (_, color) in pixels.iter() {
+            ret.extend([color.0, color.1, color.2]);
+        }
+        ret
+    }
+    /// SVG with `scale` units per cell. Runs of equally colored cells in a
+    /// row share a rectangle to keep large grids small.
+    pub fn to_svg(&self, scale: usize) -> String {
+        let (width, height) = (self.cells.width() * scale, self.cells.height() * scale);
+        let mut ret = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+             viewBox=\"0 0 {0} {1}\" shape-rendering=\"crispEdges\">\n",
+            width, height
+        );
+        for (row, cells) in self.cells.rows().enumerate() {
+            let mut start = 0;
+            while start < cells.len() {
+                let len = cells[start..]
+                    .iter()
+                    .take_while(|color| **color == cells[start])
+                    .count();
+                writeln!(
+                    ret,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    start * scale,
+                    row * scale,
+                    len * scale,
+                    scale,
+                    cells[start].hex()
+                )
+                .unwrap();
+                start += len;
+            }
+        }
+        for (points, color) in &self.paths {
+            let points = points
+                .iter()
+                .map(|pos| {
+                    format!(
+                        "{},{}",
+                        (pos.col as f64 + 0.5) * scale as f64,
+                        (pos.row as f64 + 0.5) * scale as f64
+                    )
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                ret,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+                 stroke-linejoin=\"round\" shape-rendering=\"auto\"/>",
+                points.join(" "),
+                color.hex(),
+                scale as f64 / 2.0
+            )
+            .unwrap();
+        }
+        ret.push_str("</svg>\n");
+        ret
+    }
+}
+
+/// Pixels on the straight line from `a` to `b`, both included, by
+/// Bresenham's algorithm.
+fn line(a: Pos, b: Pos) -> Vec<Pos> {
+    let (mut row, mut col) = (a.row as isize, a.col as isize);
+    let (end_row, end_col) = (b.row as isize, b.col as isize);
+    let (d_row, d_col) = ((end_row - row).abs(), -(end_col - col).abs());
+    let (step_row, step_col) = ((end_row - row).signum(), (end_col - col).signum());
+    let mut err = d_row + d_col;
+    let mut ret = vec![a];
+    while (row, col) != (end_row, end_col) {
+        if 2 * err >= d_col {
+            err += d_col;
+            row += step_row;
+        }
+        if 2 * err <= d_row {
+            err += d_row;
+            col += step_col;
+        }
+        ret.push(Pos::new(row as usize, col as usize));
+    }
+    ret
+}
+
+thread_local! {
+    static OUTPUT_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Makes `emit` write into `dir`, or nowhere.
+pub fn set_output_dir(dir: Option<PathBuf>) {
+    OUTPUT_DIR.with(|output| *output.borrow_mut() = dir);
+}
+
+fn write(dir: &Path, name: impl fmt::Display, picture: &Picture) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(format!("{}.ppm", name)), picture.to_ppm(CELL_SIZE))?;
+    fs::write(dir.join(format!("{}.svg", name)), picture.to_svg(CELL_SIZE))
+}
+
+/// Writes the picture `draw` makes as `<name>.ppm` and `<name>.svg` when
+/// rendering. `draw` is only called then, so it may be slow. Failing to
+/// write is reported without stopping the solution.
+pub fn emit(name: impl fmt::Display, draw: impl FnOnce() -> Picture) {
+    let Some(dir) = OUTPUT_DIR.with(|output| output.borrow().clone()) else {
+        return;
+    };
+    if let Err(err) = write(&dir, &name, &draw()) {
+        eprintln!("Could not render {}: {}", name, err);
+    }
+}
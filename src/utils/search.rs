@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use num::Zero;
+
+use super::counters;
+
+/// Outcome of a search: the best known distance to every visited state, the
+/// predecessor each state was reached from, and the goal state if one was
+/// reached before the search stopped.
+pub struct Paths<N, C> {
+    pub dist: HashMap<N, C>,
+    pub prev: HashMap<N, N>,
+    pub goal: Option<N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Paths<N, C> {
+    fn new() -> Paths<N, C> {
+        Paths {
+            dist: HashMap::new(),
+            prev: HashMap::new(),
+            goal: None,
+        }
+    }
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.dist.get(node).copied()
+    }
+    /// States from a start state to `node`, both ends included.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.dist.contains_key(node) {
+            return None;
+        }
+        let mut path = vec![node.clone()];
+        while let Some(parent) = self.prev.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+    pub fn goal_distance(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.distance(goal))
+    }
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.goal.as_ref().and_then(|goal| self.path_to(goal))
+    }
+}
+
+/// Unweighted breadth-first search from every state in `starts`.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+) -> Paths<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new();
+    let mut q: VecDeque<N> = VecDeque::new();
+    for start in starts {
+        if !paths.dist.contains_key(&start) {
+            paths.dist.insert(start.clone(), 0);
+            q.push_back(start);
+        }
+    }
+    while let Some(curr) = q.pop_front() {
+        let curr_dist = paths.dist[&curr];
+        for next in neighbors(&curr) {
+            if !paths.dist.contains_key(&next) {
+                paths.dist.insert(next.clone(), curr_dist + 1);
+                paths.prev.insert(next.clone(), curr.clone());
+                q.push_back(next);
+            }
+        }
+    }
+    paths
+}
+
+struct HeapEntry<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for HeapEntry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for HeapEntry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for HeapEntry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for HeapEntry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so that `BinaryHeap` pops the smallest priority first.
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// A* search. States are expanded in order of distance plus `heuristic`,
+/// which must never overestimate the remaining cost. The search stops as soon
+/// as a state satisfying `is_goal` is expanded; pass `|_| false` to explore
+/// everything reachable.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    heuristic: impl Fn(&N) -> C,
+    is_goal: impl Fn(&N) -> bool,
+) -> Paths<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = Paths::new();
+    let mut q: BinaryHeap<HeapEntry<N, C>> = BinaryHeap::new();
+    for start in starts {
+        if !paths.dist.contains_key(&start) {
+            paths.dist.insert(start.clone(), C::zero());
+            q.push(HeapEntry {
+                priority: heuristic(&start),
+                cost: C::zero(),
+                node: start,
+            });
+        }
+    }
+    while let Some(HeapEntry { cost, node, .. }) = q.pop() {
+        counters::increment("nodes popped");
+        if paths.dist[&node] < cost {
+            continue;
+        }
+        if is_goal(&node) {
+            paths.goal = Some(node);
+            break;
+        }
+        for (next, weight) in neighbors(&node) {
+            let next_cost = cost + weight;
+            if paths.dist.get(&next).is_none_or(|d| next_cost < *d) {
+                paths.dist.insert(next.clone(), next_cost);
+                paths.prev.insert(next.clone(), node.clone());
+                q.push(HeapEntry {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    paths
+}
+
+/// Dijkstra's algorithm over non-negative edge weights, stopping at the first
+/// state satisfying `is_goal`.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl Fn(&N) -> bool,
+) -> Paths<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, neighbors, |_| C::zero(), is_goal)
+}
+
+/// Shortest paths when every edge weighs either 0 or 1, given as `false` and
+/// `true` respectively, stopping at the first state satisfying `is_goal`.
+pub fn zero_one_bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    is_goal: impl Fn(&N) -> bool,
+) -> Paths<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, bool)>,
+{
+    let mut paths = Paths::new();
+    let mut q: VecDeque<(usize, N)> = VecDeque::new();
+    for start in starts {
+        if !paths.dist.contains_key(&start) {
+            paths.dist.insert(start.clone(), 0);
+            q.push_back((0, start));
+        }
+    }
+    while let Some((cost, node)) = q.pop_front() {
+        counters::increment("nodes popped");
+        if paths.dist[&node] < cost {
+            continue;
+        }
+        if is_goal(&node) {
+            paths.goal = Some(node);
+            break;
+        }
+        for (next, costly) in neighbors(&node) {
+            let next_cost = cost + usize::from(costly);
+            if paths.dist.get(&next).is_none_or(|d| next_cost < *d) {
+                paths.dist.insert(next.clone(), next_cost);
+                paths.prev.insert(next.clone(), node.clone());
+                if costly {
+                    q.push_back((next_cost, next));
+                } else {
+                    q.push_front((next_cost, next));
+                }
+            }
+        }
+    }
+    paths
+}
@@ -0,0 +1,562 @@
+use std::collections::HashMap;
+
+use super::cycle::{self, Cycle};
+use super::direction::Direction;
+use super::geometry::{Location, Orientation, Polygon};
+use super::graph::Graph;
+use super::grid::{Grid, Pos};
+use super::interval::{Interval, IntervalSet};
+use super::number_theory::{self, Congruence};
+use super::polynomial::{Polynomial, Rational};
+use super::search;
+
+/// 0, 1, 2, then 3, 4, 5, 6 over and over.
+fn rho(n: &u64) -> u64 {
+    if *n == 6 {
+        3
+    } else {
+        n + 1
+    }
+}
+
+#[test]
+fn finds_cycles() {
+    let expected = Cycle {
+        prefix: 3,
+        period: 4,
+    };
+    assert_eq!(cycle::find_cycle(0, rho), expected);
+    assert_eq!(cycle::floyd(0, rho), expected);
+    assert_eq!(cycle::brent(0, rho), expected);
+    // Only the value decides the cycle, not how many steps led there.
+    assert_eq!(
+        cycle::find_cycle_by_key((0, 0), |(n, steps)| (rho(n), steps + 1), |(n, _)| *n),
+        expected
+    );
+
+    let stuck = |n: &u64| (n + 1).min(5);
+    let fixed_point = Cycle {
+        prefix: 5,
+        period: 1,
+    };
+    assert_eq!(cycle::find_cycle(0, stuck), fixed_point);
+    assert_eq!(cycle::floyd(0, stuck), fixed_point);
+    assert_eq!(cycle::brent(0, stuck), fixed_point);
+
+    let round = |n: &u64| (n + 1) % 3;
+    let pure = Cycle {
+        prefix: 0,
+        period: 3,
+    };
+    assert_eq!(cycle::find_cycle(0, round), pure);
+    assert_eq!(cycle::floyd(0, round), pure);
+    assert_eq!(cycle::brent(0, round), pure);
+}
+
+#[test]
+fn skips_whole_periods() {
+    let cycle = Cycle {
+        prefix: 3,
+        period: 4,
+    };
+    assert_eq!(cycle.equivalent_index(2), 2);
+    assert_eq!(cycle.equivalent_index(3), 3);
+    assert_eq!(cycle.equivalent_index(7), 3);
+    assert_eq!(cycle.equivalent_index(1_000_000_001), 5);
+    assert_eq!(cycle::state_after(0, rho, 0), 0);
+    assert_eq!(cycle::state_after(0, rho, 2), 2);
+    assert_eq!(cycle::state_after(0, rho, 3), 3);
+    assert_eq!(cycle::state_after(0, rho, 6), 6);
+
+    let mut steps = 0;
+    let far = cycle::state_after(
+        0,
+        |n| {
+            steps += 1;
+            rho(n)
+        },
+        1_000_000_001,
+    );
+    assert_eq!(far, 5);
+    assert_eq!(steps, 7);
+    assert_eq!(
+        cycle::state_after_by_key((0, 0), |(n, steps)| (rho(n), steps + 1), |(n, _)| *n, 9),
+        (5, 5)
+    );
+}
+
+#[test]
+fn measures_polygons_in_both_windings() {
+    let square = Polygon::new(vec![(0, 0), (4, 0), (4, 4), (0, 4)]);
+    let mut reversed = square.clone();
+    reversed.vertices.reverse();
+    assert_eq!(square.signed_double_area(), 32);
+    assert_eq!(reversed.signed_double_area(), -32);
+    assert_eq!(square.double_area(), 32);
+    assert_eq!(reversed.double_area(), 32);
+    assert_eq!(square.orientation(), Orientation::CounterClockwise);
+    assert_eq!(reversed.orientation(), Orientation::Clockwise);
+    assert_eq!(
+        Polygon::new(vec![(0, 0), (1, 1), (2, 2)]).orientation(),
+        Orientation::Collinear
+    );
+
+    assert_eq!(square.boundary_points(), 16);
+    assert_eq!(square.interior_points(), 9);
+    assert_eq!(reversed.interior_points(), 9);
+    // The hypotenuse only passes through its own endpoints.
+    let triangle = Polygon::new(vec![(0, 0), (4, 0), (0, 3)]);
+    assert_eq!(triangle.boundary_points(), 8);
+    assert_eq!(triangle.interior_points(), 3);
+}
+
+#[test]
+fn locates_points_in_polygons() {
+    // An L whose inner corner sits at the same height as other vertices.
+    let shape = Polygon::new(vec![(0, 0), (4, 0), (4, 2), (2, 2), (2, 4), (0, 4)]);
+    assert_eq!(shape.locate((1, 3)), Location::Inside);
+    assert_eq!(shape.locate((3, 1)), Location::Inside);
+    assert_eq!(shape.locate((3, 3)), Location::Outside);
+    assert_eq!(shape.locate((-1, 2)), Location::Outside);
+    assert_eq!(shape.locate((5, 2)), Location::Outside);
+    assert_eq!(shape.locate((3, 2)), Location::Boundary);
+    assert_eq!(shape.locate((0, 1)), Location::Boundary);
+    assert_eq!(shape.locate((2, 2)), Location::Boundary);
+    assert_eq!(shape.locate((4, 0)), Location::Boundary);
+}
+
+#[test]
+fn detects_self_intersections() {
+    let bow_tie = Polygon::new(vec![(0, 0), (2, 2), (2, 0), (0, 2)]);
+    assert!(bow_tie.is_self_intersecting());
+    assert_eq!(bow_tie.signed_double_area(), 0);
+    let square = Polygon::new(vec![(0, 0), (4, 0), (4, 4), (0, 4)]);
+    assert!(!square.is_self_intersecting());
+    // Folding back along the previous edge touches more than the shared vertex.
+    assert!(Polygon::new(vec![(0, 0), (4, 0), (2, 0), (2, 3)]).is_self_intersecting());
+}
+
+#[test]
+fn turns_directions() {
+    for dir in Direction::ALL {
+        assert_eq!(Direction::ALL[dir.index()], dir);
+        assert_eq!(dir.turn_left().turn_right(), dir);
+        assert_eq!(dir.turn_left_45().turn_right_45(), dir);
+        assert_eq!(dir.reverse().reverse(), dir);
+        assert_eq!(dir.turn_right().turn_right(), dir.reverse());
+        assert_eq!(dir.turn_right_45().turn_right_45(), dir.turn_right());
+        assert_eq!(dir.turn_left_45().is_diagonal(), !dir.is_diagonal());
+        let ((d_row, d_col), (back_row, back_col)) = (dir.delta(), dir.reverse().delta());
+        assert_eq!((d_row + back_row, d_col + back_col), (0, 0));
+    }
+    assert_eq!(Direction::Up.turn_right_45(), Direction::UpRight);
+    assert_eq!(Direction::Up.turn_left_45(), Direction::UpLeft);
+    assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    assert_eq!(Direction::Up.delta(), (-1, 0));
+    assert_eq!(Direction::DownLeft.delta(), (1, -1));
+    assert_eq!(Direction::Right.advance((2, -3), 4), (2, 1));
+    assert_eq!(Direction::Left.cardinal_index(), 3);
+}
+
+#[test]
+fn parses_directions() {
+    for (chars, dir) in [
+        ("U^N", Direction::Up),
+        ("R>E", Direction::Right),
+        ("DvS", Direction::Down),
+        ("L<W", Direction::Left),
+    ] {
+        for c in chars.chars() {
+            assert_eq!(Direction::from_char(c), Some(dir), "{}", c);
+        }
+    }
+    for c in ['u', 'V', 'x', ' ', '.'] {
+        assert_eq!(Direction::from_char(c), None, "{:?}", c);
+    }
+}
+
+fn grid(text: &str) -> Grid<char> {
+    Grid::from_rows(text.lines().map(|line| line.chars().collect()).collect())
+}
+
+#[test]
+fn builds_grids_from_rows() {
+    let mut grid = grid("abc\ndef\n");
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Pos::new(1, 0)], 'd');
+    assert_eq!(grid.get(Pos::new(0, 3)), None);
+    *grid.get_mut(Pos::new(0, 2)).unwrap() = 'C';
+    assert_eq!(grid.row(0), ['a', 'b', 'C']);
+    assert_eq!(
+        grid.columns()
+            .map(|column| column.collect::<String>())
+            .collect::<Vec<_>>(),
+        ["ad", "be", "Cf"]
+    );
+    assert_eq!(grid.to_string(), "abC\ndef\n");
+    assert_eq!(Grid::<char>::from_rows(vec![]).to_string(), "");
+}
+
+#[test]
+#[should_panic(expected = "same length")]
+fn rejects_ragged_rows() {
+    grid("abc\nde");
+}
+
+#[test]
+fn steps_within_bounds() {
+    let grid = grid("abc\ndef\nghi");
+    let corner = Pos::new(0, 0);
+    assert_eq!(grid.step(corner, (-1, 0)), None);
+    assert_eq!(grid.step(corner, (0, -1)), None);
+    assert_eq!(grid.step(corner, (2, 2)), Some(Pos::new(2, 2)));
+    assert_eq!(grid.step(corner, (0, 3)), None);
+    assert_eq!(grid.step(Pos::new(2, 1), (1, 0)), None);
+    assert_eq!(grid.neighbors4(corner).count(), 2);
+    assert_eq!(grid.neighbors8(corner).count(), 3);
+    assert_eq!(grid.neighbors4(Pos::new(1, 1)).count(), 4);
+    assert_eq!(
+        grid.neighbors8(Pos::new(1, 1))
+            .map(|pos| grid[pos])
+            .collect::<String>(),
+        "bcfihgda"
+    );
+}
+
+#[test]
+fn wraps_negative_coordinates() {
+    let grid = grid("abc\ndef");
+    assert_eq!(*grid.get_wrapping(0, 0), 'a');
+    assert_eq!(*grid.get_wrapping(-1, -1), 'f');
+    assert_eq!(*grid.get_wrapping(-2, -4), 'c');
+    assert_eq!(*grid.get_wrapping(5, 7), 'e');
+}
+
+#[test]
+fn transposes_and_rotates_grids() {
+    let grid = grid("abc\ndef");
+    assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+    assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+    assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad\n");
+    assert_eq!(grid.transpose().transpose(), grid);
+    assert_eq!(grid.rotate_clockwise().rotate_counterclockwise(), grid);
+    let mut turned = grid.clone();
+    for _ in 0..4 {
+        turned = turned.rotate_clockwise();
+    }
+    assert_eq!(turned, grid);
+}
+
+#[test]
+fn dijkstra_skips_stale_entries() {
+    // `b` is queued at 5 straight from `a`, then again at 2 through `c`.
+    let edges = HashMap::from([
+        ('a', vec![('b', 5), ('c', 1)]),
+        ('c', vec![('b', 1)]),
+        ('b', vec![('d', 1)]),
+        ('d', vec![]),
+    ]);
+    let mut expanded = HashMap::new();
+    let paths = search::dijkstra(
+        ['a'],
+        |node| {
+            *expanded.entry(*node).or_insert(0) += 1;
+            edges[node].clone()
+        },
+        |node| *node == 'd',
+    );
+    assert_eq!(paths.goal_distance(), Some(3));
+    assert_eq!(paths.goal_path(), Some(vec!['a', 'c', 'b', 'd']));
+    assert_eq!(expanded.get(&'b'), Some(&1));
+}
+
+#[test]
+fn astar_stops_at_the_goal() {
+    let paths = search::astar(
+        [50],
+        |n: &i32| [(n - 1, 1), (n + 1, 1)],
+        |n| (60 - n).abs(),
+        |n| *n == 60,
+    );
+    assert_eq!(paths.goal_distance(), Some(10));
+    assert_eq!(paths.goal_path(), Some((50..=60).collect()));
+    // Going left only ever looks worse, so 49 is queued but never expanded.
+    assert!(paths.dist.contains_key(&49));
+    assert!(!paths.dist.contains_key(&48));
+    assert_eq!(paths.path_to(&48), None);
+}
+
+#[test]
+fn zero_one_bfs_prefers_free_edges() {
+    // The direct route takes fewer steps, the detour costs nothing.
+    let edges = HashMap::from([
+        ('a', vec![('b', true), ('c', false)]),
+        ('b', vec![('d', true)]),
+        ('c', vec![('e', false)]),
+        ('e', vec![('d', false), ('b', true)]),
+        ('d', vec![]),
+    ]);
+    let plain = search::bfs(['a'], |node| edges[node].iter().map(|(next, _)| *next));
+    assert_eq!(plain.distance(&'d'), Some(2));
+    assert_eq!(plain.path_to(&'d'), Some(vec!['a', 'b', 'd']));
+
+    let paths = search::zero_one_bfs(['a'], |node| edges[node].clone(), |node| *node == 'd');
+    assert_eq!(paths.goal_distance(), Some(0));
+    assert_eq!(paths.goal_path(), Some(vec!['a', 'c', 'e', 'd']));
+    assert_eq!(paths.distance(&'b'), Some(1));
+    assert_eq!(
+        search::zero_one_bfs(['a'], |node| edges[node].clone(), |_| false).distance(&'d'),
+        Some(0)
+    );
+}
+
+fn intervals(set: &IntervalSet<i64>) -> Vec<(i64, i64)> {
+    set.iter().map(|curr| (curr.start, curr.end)).collect()
+}
+
+#[test]
+fn interval_sets_merge_ranges() {
+    let set = IntervalSet::from_intervals([
+        Interval::new(10, 12),
+        Interval::new(1, 3),
+        // Adjacent to the one before, so the two become one.
+        Interval::new(3, 5),
+        // Overlaps both of the ones before and after it.
+        Interval::new(11, 21),
+        Interval::new(20, 25),
+        // Empty, so left out.
+        Interval::new(7, 7),
+        Interval::new(9, 8),
+    ]);
+    assert_eq!(intervals(&set), [(1, 5), (10, 25)]);
+    assert_eq!(set.min(), Some(1));
+    assert_eq!(
+        IntervalSet::<i64>::from_intervals([Interval::new(4, 2)]).min(),
+        None
+    );
+}
+
+#[test]
+fn interval_sets_split_ranges() {
+    let set = IntervalSet::from_intervals([Interval::new(0, 10), Interval::new(20, 30)]);
+    assert_eq!(
+        intervals(&set.remove_interval(&Interval::new(5, 25))),
+        [(0, 5), (25, 30)]
+    );
+    assert_eq!(
+        intervals(&set.intersect_interval(&Interval::inclusive(5, 24))),
+        [(5, 10), (20, 25)]
+    );
+    assert_eq!(intervals(&set.remove_interval(&Interval::new(0, 30))), []);
+    assert_eq!(intervals(&set.shift(-5)), [(-5, 5), (15, 25)]);
+    assert_eq!(
+        Interval::new(0, 10).split_at(4),
+        (Some(Interval::new(0, 4)), Some(Interval::new(4, 10)))
+    );
+    assert_eq!(
+        Interval::new(0, 10).split_at(10),
+        (Some(Interval::new(0, 10)), None)
+    );
+}
+
+#[test]
+fn combines_congruences() {
+    // The moduli share a factor of 2, which the residues agree on.
+    let combined = number_theory::crt([Congruence::new(2, 6), Congruence::new(8, 10)]);
+    assert_eq!(combined, Some(Congruence::new(8, 30)));
+    assert_eq!(combined.unwrap().first_at_least(100), 128);
+    assert_eq!(combined.unwrap().first_at_least(8), 8);
+    assert_eq!(combined.unwrap().first_at_least(-25), -22);
+    // Odd and even at once.
+    assert_eq!(
+        number_theory::crt([Congruence::new(1, 4), Congruence::new(2, 6)]),
+        None
+    );
+    assert_eq!(number_theory::crt([]), Some(Congruence::new(0, 1)));
+}
+
+#[test]
+fn combines_congruences_with_huge_moduli() {
+    // Intermediate products exceed `i128`, but the combined modulus fits.
+    let (big, power) = ((1i128 << 64) + 1, 1i128 << 62);
+    let combined = Congruence::new(12_345_678_901_234_567, big)
+        .combine(&Congruence::new(3, power))
+        .unwrap();
+    assert_eq!(combined.modulus, big * power);
+    assert_eq!(combined.residue % big, 12_345_678_901_234_567);
+    assert_eq!(combined.residue % power, 3);
+}
+
+#[test]
+#[should_panic(expected = "overflows")]
+fn rejects_combined_moduli_beyond_i128() {
+    Congruence::new(0, 1 << 100).combine(&Congruence::new(1, (1 << 40) + 1));
+}
+
+#[test]
+fn inverts_coprime_residues() {
+    assert_eq!(number_theory::mod_inverse(3, 7), Some(5));
+    assert_eq!(number_theory::mod_inverse(-3, 7), Some(2));
+    assert_eq!(number_theory::mod_inverse(10, 7), Some(5));
+    assert_eq!(number_theory::mod_inverse(1, 1), Some(0));
+    let big = (1i128 << 61) - 1;
+    let inverse = number_theory::mod_inverse(123_456_789, big).unwrap();
+    assert_eq!(number_theory::mul_mod(inverse, 123_456_789, big), 1);
+    // Sharing a factor with the modulus leaves nothing to invert.
+    assert_eq!(number_theory::mod_inverse(4, 6), None);
+    assert_eq!(number_theory::mod_inverse(0, 5), None);
+    assert_eq!(number_theory::mod_inverse(12, 6), None);
+}
+
+fn polynomial(coefficients: &[(i128, i128)]) -> Polynomial {
+    Polynomial::new(
+        coefficients
+            .iter()
+            .map(|(numer, denom)| Rational::new(*numer, *denom))
+            .collect(),
+    )
+}
+
+#[test]
+fn interpolates_polynomials() {
+    let square = Polynomial::lagrange(&[(-2, 1), (1, -2), (4, 13)]);
+    assert_eq!(square, polynomial(&[(-3, 1), (0, 1), (1, 1)]));
+    assert_eq!(square.eval_integer(-5), 22);
+
+    // Triangular numbers, x / 2 + x^2 / 2, are integers though the
+    // coefficients are not.
+    let triangular = Polynomial::fit(&[0, 1, 3, 6, 10, 15], 2).unwrap();
+    assert_eq!(triangular, polynomial(&[(0, 1), (1, 2), (1, 2)]));
+    assert_eq!(triangular.eval_integer(100), 5050);
+    assert_eq!(Polynomial::fit(&[0, 1, 3, 6], 2), None);
+}
+
+#[test]
+fn finds_dominators() {
+    let mut graph: Graph<char> = Graph::new();
+    for (from, to) in [
+        ('r', 'a'),
+        ('r', 'b'),
+        ('a', 'c'),
+        ('b', 'c'),
+        ('c', 'd'),
+        ('d', 'a'),
+        // Unreachable from the root, so it dominates nothing.
+        ('x', 'c'),
+    ] {
+        graph.connect(from, to, ());
+    }
+    let id = |label| graph.id(&label).unwrap();
+    let dominators = graph.dominators(id('r'));
+    let strict = |label| {
+        dominators
+            .strict_dominators(id(label))
+            .map(|dominator| *graph.label(dominator))
+            .collect::<String>()
+    };
+    assert_eq!(strict('a'), "r");
+    assert_eq!(strict('c'), "r");
+    assert_eq!(strict('d'), "cr");
+    assert_eq!(dominators.immediate_dominator(id('r')), None);
+    assert_eq!(dominators.immediate_dominator(id('x')), None);
+}
+
+#[test]
+fn identifies_graph_states() {
+    let mut graph: Graph<char> = Graph::new();
+    graph.connect('a', 'b', ());
+    let copy = graph.clone();
+    assert_eq!(copy.graph_id(), graph.graph_id());
+    assert_ne!(
+        Graph::<char>::new().graph_id(),
+        Graph::<char>::new().graph_id()
+    );
+    let before = graph.graph_id();
+    graph.intern('b');
+    assert_eq!(graph.graph_id(), before);
+    graph.intern('c');
+    assert_ne!(graph.graph_id(), before);
+    let (a, c) = (graph.id(&'a').unwrap(), graph.id(&'c').unwrap());
+    let before = graph.graph_id();
+    graph.add_edge(a, c, ());
+    assert_ne!(graph.graph_id(), before);
+    assert_eq!(copy.graph_id(), copy.clone().graph_id());
+}
+
+#[test]
+fn sorts_acyclic_graphs_topologically() {
+    let mut graph: Graph<u32> = Graph::new();
+    for (from, to) in [(5, 11), (7, 11), (7, 8), (3, 8), (11, 2), (8, 9), (3, 10)] {
+        graph.connect(from, to, ());
+    }
+    let order = graph.topological_sort().unwrap();
+    assert_eq!(order.len(), graph.node_count());
+    let rank = |label| order.iter().position(|id| *graph.label(*id) == label);
+    for id in graph.nodes() {
+        for next in graph.successors(id) {
+            assert!(rank(*graph.label(id)) < rank(*graph.label(next)));
+        }
+    }
+    graph.connect(9, 7, ());
+    assert_eq!(graph.topological_sort(), None);
+}
+
+#[test]
+fn finds_strongly_connected_components() {
+    let mut graph: Graph<char> = Graph::new();
+    for (from, to) in [
+        // Two cycles joined by an edge in one direction only.
+        ('a', 'b'),
+        ('b', 'c'),
+        ('c', 'a'),
+        ('c', 'd'),
+        ('d', 'e'),
+        ('e', 'd'),
+        // A self-loop and a lone node downstream of everything.
+        ('e', 'f'),
+        ('f', 'f'),
+        ('f', 'g'),
+    ] {
+        graph.connect(from, to, ());
+    }
+    let (x, y) = (graph.intern('x'), graph.intern('y'));
+    graph.add_undirected_edge(x, y, ());
+    assert_eq!(graph.in_edges(x), [(y, ())]);
+
+    let components = graph.strongly_connected_components();
+    let labels = components
+        .iter()
+        .map(|component| {
+            let mut labels = component
+                .iter()
+                .map(|id| *graph.label(*id))
+                .collect::<Vec<_>>();
+            labels.sort();
+            labels.into_iter().collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    let position = |label| labels.iter().position(|curr| *curr == label).unwrap();
+    let mut sorted = labels.clone();
+    sorted.sort();
+    assert_eq!(sorted, ["abc", "de", "f", "g", "xy"]);
+    assert!(position("abc") < position("de"));
+    assert!(position("de") < position("f"));
+    assert!(position("f") < position("g"));
+}
+
+#[test]
+fn writes_graphviz_dot() {
+    let mut graph: Graph<&str, u32> = Graph::new();
+    graph.connect("start", "say \"hi\"", 3);
+    graph.connect("start", "end", 7);
+    assert_eq!(
+        graph.to_weighted_dot(),
+        "digraph {\n    0 [label=\"start\"];\n    1 [label=\"say \\\"hi\\\"\"];\n    \
+         2 [label=\"end\"];\n    0 -> 1 [label=\"3\"];\n    0 -> 2 [label=\"7\"];\n}\n"
+    );
+    assert_eq!(
+        graph.to_dot(),
+        "digraph {\n    0 [label=\"start\"];\n    1 [label=\"say \\\"hi\\\"\"];\n    \
+         2 [label=\"end\"];\n    0 -> 1;\n    0 -> 2;\n}\n"
+    );
+}
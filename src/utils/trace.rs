@@ -0,0 +1,136 @@
+//! Diagnostics solutions can leave in place: events at a level, nested in
+//! spans, printed to stderr once enabled with the runner's `--explain`.
+//! While disabled an event or span costs a thread-local read and an atomic
+//! load, and its message is never formatted.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn parse(name: &str) -> Option<Level> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Most detailed level shown, 0 when tracing is off.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    static SPANS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    /// Most detailed level captured on this thread, overriding `MAX_LEVEL`.
+    static CAPTURE_LEVEL: Cell<Option<u8>> = const { Cell::new(None) };
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Shows events up to `level`, or none at all.
+pub fn set_max_level(level: Option<Level>) {
+    MAX_LEVEL.store(level.map_or(0, |level| level as u8), Ordering::Relaxed);
+}
+
+#[inline]
+pub fn enabled(level: Level) -> bool {
+    let max_level = CAPTURE_LEVEL
+        .with(Cell::get)
+        .unwrap_or_else(|| MAX_LEVEL.load(Ordering::Relaxed));
+    level as u8 <= max_level
+}
+
+/// Prints an event, prefixed with the spans it happened in. Use `event!`,
+/// which skips this when `level` is disabled.
+pub fn emit(level: Level, message: fmt::Arguments) {
+    let path = SPANS.with(|spans| {
+        spans
+            .borrow()
+            .iter()
+            .map(|span| format!("{} > ", span))
+            .collect::<String>()
+    });
+    let line = format!("[{}] {}{}", level, path, message);
+    CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(lines) => lines.push(line),
+        None => eprintln!("{}", line),
+    });
+}
+
+/// Open span, closed when dropped.
+pub struct Span(());
+
+/// Opens a span called `name`. Use `span!`, which skips this when `level`
+/// is disabled.
+pub fn enter(name: String) -> Span {
+    SPANS.with(|spans| spans.borrow_mut().push(name));
+    Span(())
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        SPANS.with(|spans| spans.borrow_mut().pop());
+    }
+}
+
+/// `event!(level, format, args...)` emits a formatted event if `level` is
+/// enabled.
+macro_rules! event {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::utils::trace::enabled($level) {
+            $crate::utils::trace::emit($level, format_args!($($arg)+));
+        }
+    };
+}
+
+/// `span!(level, format, args...)` opens a span named by the formatted
+/// arguments if `level` is enabled. Events until the returned guard is
+/// dropped are prefixed with its name.
+macro_rules! span {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::utils::trace::enabled($level) {
+            Some($crate::utils::trace::enter(format!($($arg)+)))
+        } else {
+            None
+        }
+    };
+}
+
+pub(crate) use {event, span};
+
+/// Runs `f` with tracing up to `level` on this thread only, returning the
+/// lines it traced instead of printing them.
+#[cfg(test)]
+pub fn capture<T>(level: Level, f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let previous_level = CAPTURE_LEVEL.with(|max_level| max_level.replace(Some(level as u8)));
+    let previous_lines = CAPTURED.with(|captured| captured.replace(Some(Vec::new())));
+    let ret = f();
+    CAPTURE_LEVEL.with(|max_level| max_level.set(previous_level));
+    let lines = CAPTURED.with(|captured| captured.replace(previous_lines).unwrap_or_default());
+    (ret, lines)
+}